@@ -1,11 +1,10 @@
-use aoc2021::Input;
+use crate::Input;
 use itertools::Itertools;
-use std::io::BufRead;
 
-pub fn part1(reader: Input) -> anyhow::Result<u32> {
-    let r = reader
-        .lines()
-        .map(|x| x.unwrap().parse::<u32>().unwrap())
+pub fn part1(mut reader: Input) -> anyhow::Result<u32> {
+    let values = reader.lines_parsed::<u32>().collect::<anyhow::Result<Vec<_>>>()?;
+    let r = values
+        .into_iter()
         .scan(None, |state, x| {
             let previous = state.take();
             *state = Some(x);
@@ -20,10 +19,10 @@ pub fn part1(reader: Input) -> anyhow::Result<u32> {
     Ok(r)
 }
 
-pub fn part2(reader: Input) -> anyhow::Result<u32> {
-    let r = reader
-        .lines()
-        .map(|x| x.unwrap().parse::<u32>().unwrap())
+pub fn part2(mut reader: Input) -> anyhow::Result<u32> {
+    let values = reader.lines_parsed::<u32>().collect::<anyhow::Result<Vec<_>>>()?;
+    let r = values
+        .into_iter()
         // Pad with an additional useless value to yield the last window,
         // since we're using 4 size windows but we only really need 3 values
         // for a full window.
@@ -44,6 +43,17 @@ pub fn part2(reader: Input) -> anyhow::Result<u32> {
     Ok(r)
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+199
+200
+208
+210
+200
+207
+240
+269
+260
+263
+" => { part1: 7, part2: 5 }
 }
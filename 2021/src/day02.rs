@@ -1,5 +1,4 @@
-use aoc2021::Input;
-use std::io::BufRead;
+use crate::Input;
 
 #[derive(Copy, Clone, Debug)]
 enum Command {
@@ -59,24 +58,29 @@ impl State {
     }
 }
 
-pub fn part1(reader: Input) -> anyhow::Result<u32> {
+pub fn part1(mut reader: Input) -> anyhow::Result<u32> {
     let State { forward, down, .. } = reader
-        .lines()
-        .map(|line| line.unwrap().parse().unwrap())
-        .fold(State::default(), |state, cmd| state.apply_part1(cmd));
+        .lines_parsed()
+        .try_fold(State::default(), |state, cmd| Ok::<_, anyhow::Error>(state.apply_part1(cmd?)))?;
 
     Ok(forward * down)
 }
 
-pub fn part2(reader: Input) -> anyhow::Result<u32> {
+pub fn part2(mut reader: Input) -> anyhow::Result<u32> {
     let State { forward, down, .. } = reader
-        .lines()
-        .map(|line| line.unwrap().parse().unwrap())
-        .fold(State::default(), |state, cmd| state.apply_part2(cmd));
+        .lines_parsed()
+        .try_fold(State::default(), |state, cmd| Ok::<_, anyhow::Error>(state.apply_part2(cmd?)))?;
 
     Ok(forward * down)
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2
+" => { part1: 150, part2: 900 }
 }
@@ -1,4 +1,4 @@
-use aoc2021::Input;
+use crate::Input;
 use itertools::Itertools;
 use std::io::BufRead;
 
@@ -15,7 +15,7 @@ pub fn part1(reader: Input) -> anyhow::Result<u32> {
                 })
                 .collect::<Vec<_>>()
         })
-        .fold1(|a, b| {
+        .reduce(|a, b| {
             a.iter()
                 .zip(b.iter())
                 .map(|(x, y)| x + y)
@@ -85,6 +85,19 @@ fn select<T>(ones: Vec<T>, zeros: Vec<T>, prefer_majority: bool) -> (Vec<T>, Vec
     }
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+00100
+11110
+10110
+10111
+10101
+01111
+00111
+11100
+10000
+11001
+00010
+01010
+" => { part1: 198, part2: 230 }
 }
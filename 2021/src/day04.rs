@@ -1,5 +1,5 @@
-use aoc2021::Input;
-use std::io::BufRead;
+use crate::Input;
+use anyhow::Context;
 
 enum Field {
     Unmarked(u8),
@@ -38,19 +38,17 @@ impl std::fmt::Debug for Field {
 struct Board(Vec<Vec<Field>>);
 
 impl Board {
-    fn parse_one(lines: &mut impl Iterator<Item = String>) -> Option<Self> {
+    fn parse(lines: &[String]) -> anyhow::Result<Self> {
         let field = lines
-            .skip_while(|line| line.trim().is_empty())
-            .take_while(|line| !line.trim().is_empty())
+            .iter()
             .map(|line| {
                 line.split_whitespace()
-                    .map(|num| num.parse::<u8>().unwrap())
-                    .map(Field::Unmarked)
-                    .collect::<Vec<_>>()
+                    .map(|num| Ok(Field::Unmarked(num.parse::<u8>()?)))
+                    .collect::<anyhow::Result<Vec<_>>>()
             })
-            .collect::<Vec<_>>();
+            .collect::<anyhow::Result<Vec<_>>>()?;
 
-        (!field.is_empty()).then(|| Board(field))
+        Ok(Board(field))
     }
 
     fn mark(&mut self, number: u8) -> bool {
@@ -98,15 +96,31 @@ impl std::fmt::Debug for Board {
     }
 }
 
-pub fn part1(reader: Input) -> anyhow::Result<u32> {
-    let mut input = reader.lines().map(|line| line.unwrap());
-    let numbers = input.next().unwrap();
+fn read(reader: &mut Input) -> anyhow::Result<(Vec<u8>, Vec<Board>)> {
+    let mut blocks = reader.blocks();
+    let numbers = blocks
+        .next()
+        .context("Missing drawn numbers")??
+        .join(",")
+        .split(',')
+        .map(|num| num.parse::<u8>())
+        .collect::<Result<_, _>>()
+        .context("Failed to parse drawn numbers")?;
+
+    let boards = blocks
+        .enumerate()
+        .map(|(i, block)| {
+            Board::parse(&block?).with_context(|| format!("Failed to parse board {}", i + 1))
+        })
+        .collect::<anyhow::Result<_>>()?;
+
+    Ok((numbers, boards))
+}
 
-    let mut boards = std::iter::repeat(0)
-        .map_while(|_| Board::parse_one(&mut input))
-        .collect::<Vec<_>>();
+pub fn part1(mut reader: Input) -> anyhow::Result<u32> {
+    let (numbers, mut boards) = read(&mut reader)?;
 
-    for number in numbers.split(',').map(|num| num.parse::<u8>().unwrap()) {
+    for number in numbers {
         for board in boards.iter_mut() {
             if board.mark(number) {
                 return Ok(board.score() * number as u32);
@@ -117,16 +131,11 @@ pub fn part1(reader: Input) -> anyhow::Result<u32> {
     anyhow::bail!("no solution found")
 }
 
-pub fn part2(reader: Input) -> anyhow::Result<u32> {
-    let mut input = reader.lines().map(|line| line.unwrap());
-    let numbers = input.next().unwrap();
-
-    let mut boards = std::iter::repeat(0)
-        .map_while(|_| Board::parse_one(&mut input))
-        .map(Some)
-        .collect::<Vec<_>>();
+pub fn part2(mut reader: Input) -> anyhow::Result<u32> {
+    let (numbers, boards) = read(&mut reader)?;
+    let mut boards = boards.into_iter().map(Some).collect::<Vec<_>>();
 
-    for number in numbers.split(',').map(|num| num.parse::<u8>().unwrap()) {
+    for number in numbers {
         let is_last = boards.len() == 1;
         for board in boards.iter_mut() {
             if board.as_mut().unwrap().mark(number) {
@@ -134,7 +143,6 @@ pub fn part2(reader: Input) -> anyhow::Result<u32> {
                 if is_last {
                     return Ok(board.score() * number as u32);
                 }
-                break;
             }
         }
         boards.retain(|b| b.is_some());
@@ -143,6 +151,26 @@ pub fn part2(reader: Input) -> anyhow::Result<u32> {
     anyhow::bail!("no solution found")
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7
+" => { part1: 4512, part2: 1924 }
 }
@@ -1,16 +1,11 @@
-use aoc2021::Input;
+use crate::grid::Grid;
+use crate::Input;
 use std::io::BufRead;
 
 type Board = Vec<Vec<u8>>;
 
-fn transpose<T: Copy>(inp: &[Vec<T>]) -> Vec<Vec<T>> {
-    (0..inp.len())
-        .map(|i| inp.iter().map(|x| x[i]).collect())
-        .collect()
-}
-
 fn parse_one(lines: &mut impl Iterator<Item = String>) -> Option<Board> {
-    let mut board = lines
+    let rows = lines
         .skip_while(|line| line.trim().is_empty())
         .take_while(|line| !line.trim().is_empty())
         .map(|line| {
@@ -20,11 +15,20 @@ fn parse_one(lines: &mut impl Iterator<Item = String>) -> Option<Board> {
         })
         .collect::<Vec<_>>();
 
-    if board.is_empty() {
+    if rows.is_empty() {
         return None;
     }
 
-    board.extend(transpose(&board));
+    // The rows followed by the columns, every line of the board which can win.
+    let (width, height) = (rows[0].len(), rows.len());
+    let grid = Grid::new(rows.concat(), width, height);
+    let columns = grid.transpose();
+    let board = grid
+        .data()
+        .chunks(width)
+        .chain(columns.data().chunks(height))
+        .map(<[u8]>::to_vec)
+        .collect();
     Some(board)
 }
 
@@ -98,6 +102,26 @@ pub fn part2(reader: Input) -> anyhow::Result<u32> {
     anyhow::bail!("no solution found")
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7
+" => { part1: 4512, part2: 1924 }
 }
@@ -1,6 +1,6 @@
-use aoc2021::Input;
+use crate::sparse_grid::SparseGrid;
+use crate::Input;
 use either::{Left, Right};
-use itertools::Itertools;
 use std::cmp;
 use std::io::BufRead;
 
@@ -57,35 +57,41 @@ impl Line {
     }
 }
 
+fn count_overlaps(lines: impl Iterator<Item = Line>) -> usize {
+    let mut vents = SparseGrid::new();
+    for point in lines.flat_map(|line| line.iter()) {
+        *vents.get_or_insert_with((point.x as isize, point.y as isize), || 0) += 1;
+    }
+
+    vents.cells().filter(|(_, &count)| count > 1).count()
+}
+
 pub fn part1(reader: Input) -> anyhow::Result<usize> {
-    let r = reader
+    let lines = reader
         .lines()
         .map(|line| Line::parse(&line.unwrap()))
-        .filter(|line| line.is_horz_or_vert())
-        .flat_map(|line| line.iter())
-        .into_grouping_map_by(|point| *point)
-        .fold(0, |count, _key, _value| count + 1)
-        .values()
-        .filter(|count| **count > 1)
-        .count();
+        .filter(|line| line.is_horz_or_vert());
 
-    Ok(r)
+    Ok(count_overlaps(lines))
 }
 
 pub fn part2(reader: Input) -> anyhow::Result<usize> {
-    let r = reader
-        .lines()
-        .map(|line| Line::parse(&line.unwrap()))
-        .flat_map(|line| line.iter())
-        .into_grouping_map_by(|point| *point)
-        .fold(0, |count, _key, _value| count + 1)
-        .values()
-        .filter(|count| **count > 1)
-        .count();
+    let lines = reader.lines().map(|line| Line::parse(&line.unwrap()));
 
-    Ok(r)
+    Ok(count_overlaps(lines))
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+0,9 -> 5,9
+8,0 -> 0,8
+9,4 -> 3,4
+2,2 -> 2,1
+7,0 -> 7,4
+6,4 -> 2,0
+0,9 -> 2,9
+3,4 -> 1,4
+0,0 -> 8,8
+5,5 -> 8,2
+" => { part1: 5, part2: 12 }
 }
@@ -1,7 +1,6 @@
-use aoc2021::Input;
+use crate::Input;
 use itertools::Itertools;
 use std::collections::HashMap;
-use std::io::BufRead;
 
 fn calculate(start: u8, days: usize) -> u32 {
     let mut fishes = vec![0];
@@ -49,15 +48,8 @@ fn calculate2internal(days: i64, cache: &mut HashMap<i64, u64>) -> u64 {
     a + b + 1
 }
 
-pub fn part1(reader: Input) -> anyhow::Result<u64> {
-    let input: Vec<_> = reader
-        .lines()
-        .next()
-        .unwrap()
-        .unwrap()
-        .split(',')
-        .map(|x| x.parse::<u8>().unwrap())
-        .collect();
+pub fn part1(mut reader: Input) -> anyhow::Result<u64> {
+    let input = reader.csv_numbers::<u8>()?;
 
     let calculated: HashMap<_, _> = input
         .iter()
@@ -74,21 +66,17 @@ pub fn part1(reader: Input) -> anyhow::Result<u64> {
     Ok(result as u64)
 }
 
-pub fn part2(reader: Input) -> anyhow::Result<u64> {
+pub fn part2(mut reader: Input) -> anyhow::Result<u64> {
     let mut cache = HashMap::new();
     let result = reader
-        .lines()
-        .next()
-        .unwrap()
-        .unwrap()
-        .split(',')
-        .map(|x| x.parse::<u8>().unwrap())
+        .csv_numbers::<u8>()?
+        .into_iter()
         .map(move |x| calculate2(x, 256, &mut cache))
         .sum();
 
     Ok(result)
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "3,4,3,1,2" => { part1: 5934, part2: 26984457539 }
 }
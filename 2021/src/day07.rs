@@ -1,13 +1,8 @@
-use aoc2021::Input;
+use crate::Input;
 use itertools::Itertools;
-use std::io::BufRead;
 
 fn abs_diff(a: u32, b: u32) -> u32 {
-    if a > b {
-        a - b
-    } else {
-        b - a
-    }
+    a.abs_diff(b)
 }
 
 fn search_minimum<F: Fn(u32) -> u32>(mut min: u32, mut max: u32, mapper: F) -> u32 {
@@ -106,34 +101,20 @@ fn fuel_cost2(target: u32, input: &[u32]) -> BinSearch<u32> {
     }
 }
 
-pub fn part1(reader: Input) -> anyhow::Result<u32> {
-    let input: Vec<u32> = reader
-        .lines()
-        .next()
-        .unwrap()
-        .unwrap()
-        .split(',')
-        .map(|x| x.parse().unwrap())
-        .collect();
+pub fn part1(mut reader: Input) -> anyhow::Result<u32> {
+    let input = reader.csv_numbers::<u32>()?;
 
     let (min, max) = input.iter().minmax().into_option().unwrap();
     Ok(search_minimum(*min, *max, |x| fuel_cost(x, &input)))
 }
 
-pub fn part2(reader: Input) -> anyhow::Result<u32> {
-    let input: Vec<u32> = reader
-        .lines()
-        .next()
-        .unwrap()
-        .unwrap()
-        .split(',')
-        .map(|x| x.parse().unwrap())
-        .collect();
+pub fn part2(mut reader: Input) -> anyhow::Result<u32> {
+    let input = reader.csv_numbers::<u32>()?;
 
     let (min, max) = input.iter().minmax().into_option().unwrap();
     Ok(search_minimum2(*min, *max, |x| fuel_cost2(x, &input)))
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "16,1,2,0,4,2,7,1,2,14" => { part1: 37, part2: 168 }
 }
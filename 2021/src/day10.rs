@@ -1,4 +1,4 @@
-use aoc2021::Input;
+use crate::Input;
 use itertools::Itertools;
 use std::io::BufRead;
 
@@ -78,6 +78,17 @@ pub fn part2(reader: Input) -> anyhow::Result<usize> {
     Ok(r[r.len() / 2])
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+[({(<(())[]>[[{[]{<()<>>
+[(()[<>])]({[<{<<[]>>(
+{([(<{}[<>[]}>{[]{[(<()>
+(((({<>}<{<{<>}{[]{[]{}
+[[<[([]))<([[{}[[()]]]
+[{[{({}]{}}([{[{{{}}([]
+{<[[]]>}<{[{[{[]{()[[[]
+[<(<(<(<{}))><([]([]()
+<{([([[(<>()){}]>(<<{{
+<{([{{}}[<[[[<>{}]]]>[]]
+" => { part1: 26397, part2: 288957 }
 }
@@ -1,73 +1,78 @@
-use aoc2021::{grid, Input};
+use crate::automaton::{Automaton, Neighborhood, Rule};
+use crate::grid::{self, Border};
+use crate::Input;
 
-type Grid = grid::Grid<u8>;
+/// Energy level of an octopus which flashed in the current step.
+const FLASHED: u8 = u8::MAX;
 
-macro_rules! try_flash {
-    ($grid:ident, $x:expr, $y: expr) => {
-        if let Some(value) = $grid.get_mut($x, $y) {
-            *value += 1;
-            if *value == 10 {
-                flash(&mut $grid, $x, $y)
-            }
-        }
-    };
+fn is_flashing(energy: u8) -> bool {
+    energy > 9 && energy != FLASHED
 }
 
-fn flash(mut grid: &mut Grid, x: usize, y: usize) {
-    try_flash!(grid, x - 1, y);
-    try_flash!(grid, x + 1, y);
-    try_flash!(grid, x, y - 1);
-    try_flash!(grid, x, y + 1);
-    try_flash!(grid, x - 1, y - 1);
-    try_flash!(grid, x - 1, y + 1);
-    try_flash!(grid, x + 1, y - 1);
-    try_flash!(grid, x + 1, y + 1);
+/// Every step increases the energy levels by one, then octopuses above 9 flash
+/// and increase their neighbours, which may flash in turn, and finally all
+/// octopuses which flashed are reset to 0.
+#[derive(Default)]
+struct Octopuses {
+    flashes: usize,
 }
 
-pub fn part1(reader: Input) -> anyhow::Result<usize> {
-    let mut grid: Grid = reader.into();
+impl Rule<u8> for Octopuses {
+    fn phases(&self) -> usize {
+        3
+    }
 
-    let mut flashes = 0;
-    for _ in 0..100 {
-        for (x, y) in grid.indices() {
-            try_flash!(grid, x, y);
-        }
+    fn repeat(&self, phase: usize) -> bool {
+        phase == 1
+    }
 
-        for (x, y) in grid.indices() {
-            if grid[(x, y)] > 9 {
-                grid[(x, y)] = 0;
-                flashes += 1;
+    fn update(&mut self, phase: usize, cells: &Neighborhood<'_, u8>) -> u8 {
+        let energy = cells.center();
+        match phase {
+            0 => energy + 1,
+            1 if energy == FLASHED => energy,
+            1 if is_flashing(energy) => FLASHED,
+            1 => energy + cells.neighbors().filter(|&e| is_flashing(e)).count() as u8,
+            _ if energy == FLASHED => {
+                self.flashes += 1;
+                0
             }
+            _ => energy,
         }
     }
-
-    Ok(flashes)
 }
 
-pub fn part2(reader: Input) -> anyhow::Result<usize> {
-    let mut grid: Grid = reader.into();
+pub fn part1(mut reader: Input) -> anyhow::Result<usize> {
+    let grid = reader.grid_with(grid::digit)?;
 
-    for step in 1.. {
-        for (x, y) in grid.indices() {
-            try_flash!(grid, x, y);
-        }
+    let mut octopuses = Octopuses::default();
+    Automaton::new(grid, 1, Border::Constant(0)).run(&mut octopuses, 100);
 
-        let mut flashes = 0;
-        for (x, y) in grid.indices() {
-            if grid[(x, y)] > 9 {
-                grid[(x, y)] = 0;
-                flashes += 1;
-            }
-        }
+    Ok(octopuses.flashes)
+}
 
-        if flashes == grid.size() {
-            return Ok(step);
-        }
-    }
+pub fn part2(mut reader: Input) -> anyhow::Result<usize> {
+    let grid = reader.grid_with(grid::digit)?;
+
+    let step = Automaton::new(grid, 1, Border::Constant(0))
+        .run_until(&mut Octopuses::default(), |grid| {
+            grid.data().iter().all(|&energy| energy == 0)
+        });
 
-    unreachable!();
+    Ok(step)
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526
+" => { part1: 1656, part2: 195 }
 }
@@ -1,4 +1,4 @@
-use aoc2021::Input;
+use crate::Input;
 use std::collections::{HashMap, HashSet};
 use std::io::BufRead;
 
@@ -142,6 +142,26 @@ pub fn part2(reader: Input) -> anyhow::Result<usize> {
     Ok(visit2(&start, &caves, &mut Vec::new(), None))
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    small: "
+start-A
+start-b
+A-c
+A-b
+b-d
+A-end
+b-end
+" => { part1: 10, part2: 36 }
+    larger: "
+dc-end
+HN-start
+start-kj
+dc-start
+dc-HN
+LN-dc
+HN-end
+kj-sa
+kj-HN
+kj-dc
+" => { part1: 19, part2: 103 }
 }
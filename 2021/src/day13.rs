@@ -1,58 +1,73 @@
-use aoc2021::Input;
-use std::collections::HashSet;
-use std::fmt::Display;
-use std::io::BufRead;
+use crate::sparse_grid::SparseGrid;
+use crate::Input;
+use anyhow::Context;
 
+/// The dots on the transparent paper.
+///
+/// `max_x` and `max_y` bound the paper, after folding the paper ends before the
+/// last fold lines. They can not be taken from the bounds of `dots`: a fold
+/// keeps the paper as large as the part in front of the fold line, even when
+/// its last rows or columns have no dots, and those empty lines are part of
+/// the printed code.
 #[derive(Debug)]
 struct Paper {
-    dots: HashSet<(u64, u64)>,
-    max_x: u64,
-    max_y: u64,
+    dots: SparseGrid<char>,
+    max_x: isize,
+    max_y: isize,
 }
 
 impl Paper {
-    fn read(reader: &mut Input) -> Self {
-        let mut dots = HashSet::new();
-        let lines = reader.lines().map(|line| line.unwrap());
+    fn parse(lines: &[String]) -> anyhow::Result<Self> {
+        let mut dots = SparseGrid::new();
 
         let mut max_x = 0;
         let mut max_y = 0;
-        for line in lines.take_while(|x| !x.is_empty()) {
-            let (x, y) = line.split_once(",").unwrap();
-            let x = x.parse::<u64>().unwrap();
-            let y = y.parse::<u64>().unwrap();
-            dots.insert((x, y));
+        for line in lines {
+            let (x, y) = line
+                .split_once(',')
+                .with_context(|| format!("Invalid dot {line:?}"))?;
+            let (x, y) = (x.parse()?, y.parse()?);
+            dots.insert((x, y), '#');
             max_x = max_x.max(x);
             max_y = max_y.max(y);
         }
 
-        Self { dots, max_x, max_y }
+        Ok(Self { dots, max_x, max_y })
     }
 
-    fn fold_y(&mut self, fold_y: u64) {
+    fn fold(&mut self, instruction: &str) -> anyhow::Result<()> {
+        match instruction.trim_start_matches("fold along ").split_once('=') {
+            Some(("x", value)) => self.fold_x(value.parse()?),
+            Some(("y", value)) => self.fold_y(value.parse()?),
+            _ => anyhow::bail!("Invalid fold instruction {instruction:?}"),
+        }
+        Ok(())
+    }
+
+    fn fold_y(&mut self, fold_y: isize) {
         self.dots = self
             .dots
-            .iter()
-            .map(|&(x, y)| {
+            .cells()
+            .map(|((x, y), &dot)| {
                 if y > fold_y {
-                    (x, 2 * fold_y - y)
+                    ((x, 2 * fold_y - y), dot)
                 } else {
-                    (x, y)
+                    ((x, y), dot)
                 }
             })
             .collect();
         self.max_y = fold_y;
     }
 
-    fn fold_x(&mut self, fold_x: u64) {
+    fn fold_x(&mut self, fold_x: isize) {
         self.dots = self
             .dots
-            .iter()
-            .map(|&(x, y)| {
+            .cells()
+            .map(|((x, y), &dot)| {
                 if x > fold_x {
-                    (2 * fold_x - x, y)
+                    ((2 * fold_x - x, y), dot)
                 } else {
-                    (x, y)
+                    ((x, y), dot)
                 }
             })
             .collect();
@@ -60,14 +75,13 @@ impl Paper {
     }
 }
 
-impl Display for Paper {
+impl std::fmt::Display for Paper {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for y in 0..self.max_y {
             for x in 0..self.max_x {
-                if self.dots.contains(&(x, y)) {
-                    write!(f, "#")?;
-                } else {
-                    write!(f, " ")?;
+                match self.dots.get((x, y)) {
+                    Some(&dot) => write!(f, "{dot}")?,
+                    None => write!(f, " ")?,
                 }
             }
             writeln!(f)?;
@@ -76,34 +90,55 @@ impl Display for Paper {
     }
 }
 
+fn read(reader: &mut Input) -> anyhow::Result<(Paper, Vec<String>)> {
+    let mut blocks = reader.blocks();
+    let paper = Paper::parse(&blocks.next().context("Missing dots")??)?;
+    let folds = blocks.next().transpose()?.unwrap_or_default();
+    Ok((paper, folds))
+}
+
 pub fn part1(mut reader: Input) -> anyhow::Result<String> {
-    let mut paper = Paper::read(&mut reader);
+    let (mut paper, folds) = read(&mut reader)?;
 
-    if let Some(line) = reader.lines().map(|line| line.unwrap()).next() {
-        match line.trim_start_matches("fold along ").split_once("=") {
-            Some(("x", value)) => paper.fold_x(value.parse().unwrap()),
-            Some(("y", value)) => paper.fold_y(value.parse().unwrap()),
-            _ => unreachable!(),
-        }
+    if let Some(fold) = folds.first() {
+        paper.fold(fold)?;
     }
 
     Ok(paper.dots.len().to_string())
 }
 
 pub fn part2(mut reader: Input) -> anyhow::Result<String> {
-    let mut paper = Paper::read(&mut reader);
+    let (mut paper, folds) = read(&mut reader)?;
 
-    for line in reader.lines().map(|line| line.unwrap()) {
-        match line.trim_start_matches("fold along ").split_once("=") {
-            Some(("x", value)) => paper.fold_x(value.parse().unwrap()),
-            Some(("y", value)) => paper.fold_y(value.parse().unwrap()),
-            _ => unreachable!(),
-        }
+    for fold in &folds {
+        paper.fold(fold)?;
     }
 
     Ok(paper.to_string())
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+6,10
+0,14
+9,10
+0,3
+10,4
+4,11
+6,0
+6,12
+4,1
+0,13
+10,12
+3,4
+3,0
+8,4
+1,10
+2,14
+8,10
+9,0
+
+fold along y=7
+fold along x=5
+" => { part1: "17", part2: "#####\n#   #\n#   #\n#   #\n#####\n     \n     \n" }
 }
@@ -1,4 +1,4 @@
-use aoc2021::Input;
+use crate::Input;
 use itertools::Itertools;
 use std::collections::HashMap;
 use std::io::BufRead;
@@ -131,6 +131,25 @@ pub fn part2(reader: Input) -> anyhow::Result<usize> {
     Ok(expand(&template, &input, 40))
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+NNCB
+
+CH -> B
+HH -> N
+CB -> H
+NH -> C
+HB -> C
+HC -> B
+HN -> C
+NN -> C
+BH -> H
+NC -> B
+NB -> B
+BN -> B
+BB -> N
+BC -> B
+CC -> N
+CN -> C
+" => { part1: 1588, part2: 2188189693529 }
 }
@@ -1,118 +1,84 @@
-use aoc2021::{grid, Input};
-use std::collections::BinaryHeap;
+use crate::{cli, grid, search, Input};
+use anyhow::Context;
+use std::fmt::Display;
 
 type Grid = grid::Grid<u8>;
 
-fn abs_diff(a: usize, b: usize) -> usize {
-    if a > b {
-        a - b
-    } else {
-        b - a
-    }
-}
+type Path = search::Path<(usize, usize), usize>;
 
-struct Node {
-    pos: (usize, usize),
-    cost: usize,
-    total_cost: usize,
-}
+fn find_shortest(grid: &Grid) -> Option<Path> {
+    let destination = (grid.width() - 1, grid.height() - 1);
 
-impl Node {
-    fn new(pos: (usize, usize), cost: usize, estimated: usize) -> Self {
-        Self {
-            pos,
-            cost,
-            total_cost: cost + estimated,
-        }
-    }
+    search::astar(
+        (0, 0),
+        search::grid_successors(grid, |_, &risk| Some(risk as usize)),
+        |&pos| search::manhattan(pos, destination),
+        |&pos| pos == destination,
+    )
 }
 
-impl PartialEq for Node {
-    fn eq(&self, other: &Self) -> bool {
-        self.pos == other.pos
-    }
+/// A risk level of the rendered cave, highlighted if it is on the path.
+struct Rendered {
+    risk: u8,
+    on_path: bool,
 }
 
-impl Eq for Node {}
-
-impl PartialOrd for Node {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+impl Display for Rendered {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.on_path {
+            write!(f, "\x1b[1;31m{}\x1b[0m", self.risk)
+        } else {
+            write!(f, "{}", self.risk)
+        }
     }
 }
 
-impl Ord for Node {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.total_cost.cmp(&self.total_cost)
+fn visualize(grid: &Grid, path: &Path) {
+    let data = grid
+        .data()
+        .iter()
+        .map(|&risk| Rendered {
+            risk,
+            on_path: false,
+        })
+        .collect();
+    let mut rendered = grid::Grid::new(data, grid.width(), grid.height());
+    for &pos in &path.states {
+        rendered[pos].on_path = true;
     }
+    eprintln!("{rendered}");
 }
 
-fn find_shortest(grid: &Grid) -> Option<usize> {
-    let destination = (grid.width() - 1, grid.height() - 1);
-
-    let mut checked = grid::Grid::new(vec![false; grid.size()], grid.width(), grid.height());
-    let mut candidates = BinaryHeap::with_capacity(grid.width() * grid.height() / 5);
-
-    let est_cost = |(x, y): (usize, usize)| -> usize {
-        abs_diff(x, destination.0) + abs_diff(y, destination.1)
-    };
-    candidates.push(Node::new((0, 0), 0, est_cost((0, 0))));
-
-    // A*
-    loop {
-        let current = if let Some(current) = candidates.pop() {
-            current
-        } else {
-            break None;
-        };
-
-        if current.pos == destination {
-            break Some(current.cost);
-        }
-
-        checked[current.pos] = true;
-
-        let (x, y) = current.pos;
-        let more_candidates = [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
-            .into_iter()
-            .filter(|&(x, y)| !checked.get(x, y).unwrap_or(&true))
-            .map(|new_pos| {
-                Node::new(
-                    new_pos,
-                    current.cost + grid[new_pos] as usize,
-                    est_cost(new_pos),
-                )
-            });
-
-        candidates.extend(more_candidates);
+fn solve(grid: &Grid) -> anyhow::Result<usize> {
+    let path = find_shortest(grid).context("No path through the cave")?;
+    if cli::visualize() {
+        visualize(grid, &path);
     }
+    Ok(path.cost)
 }
 
-pub fn part1(reader: Input) -> anyhow::Result<usize> {
-    let grid: Grid = reader.into();
-    Ok(find_shortest(&grid).unwrap())
+pub fn part1(mut reader: Input) -> anyhow::Result<usize> {
+    let grid = reader.grid_with(grid::digit)?;
+    solve(&grid)
 }
 
-pub fn part2(reader: Input) -> anyhow::Result<usize> {
-    let grid: Grid = reader.into();
-    let width = grid.width();
-    let height = grid.height();
-
-    let mut data = Vec::new();
-    data.resize(grid.size() * 5 * 5, 0);
-    let mut new_grid = Grid::new(data, grid.width() * 5, grid.height() * 5);
-    for (x, y, &value) in grid.values() {
-        for x_diff in 0..5 {
-            for y_diff in 0..5 {
-                new_grid[(x + width * x_diff, y + height * y_diff)] =
-                    (value + x_diff as u8 + y_diff as u8 - 1) % 9 + 1;
-            }
-        }
-    }
-
-    Ok(find_shortest(&new_grid).unwrap())
+pub fn part2(mut reader: Input) -> anyhow::Result<usize> {
+    let grid = reader.grid_with(grid::digit)?;
+    let grid = grid.tile(5, 5, |&risk, tx, ty| (risk + tx as u8 + ty as u8 - 1) % 9 + 1);
+    solve(&grid)
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+1163751742
+1381373672
+2136511328
+3694931569
+7463417111
+1319128137
+1359912421
+3125421639
+1293138521
+2311944581
+" => { part1: 40, part2: 315 }
 }
@@ -1,38 +1,137 @@
-use aoc2021::Input;
-use itertools::Itertools;
+use crate::{cli, Input};
 use nom::bits::complete::take;
-use nom::IResult;
+use nom::error::{ErrorKind, ParseError};
+use std::fmt;
 use std::io::Read;
 
 type Bits<'a> = (&'a [u8], usize);
 
-fn hex_value(v: u8) -> u8 {
-    match v {
-        b'0'..=b'9' => v - b'0',
-        b'A'..=b'F' => v - b'A' + 10,
-        _ => panic!("invalid hex digit {}", v),
+/// How deep packets may be nested, parsing and evaluating recurse once per level.
+pub const MAX_DEPTH: usize = 256;
+
+/// Why decoding a transmission failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BitsErrorKind {
+    InvalidHex(char),
+    UnexpectedEnd,
+    InvalidOperator(u8),
+    /// A literal value with more than 64 bits, also with the `bigint` feature
+    /// which only makes the results of operators unbounded.
+    LiteralTooLong,
+    /// The sub-packets of an operator with length type id 0 do not add up
+    /// to the announced number of bits.
+    LengthMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    /// An operator with a number of sub-packets it can not be evaluated on,
+    /// comparisons need exactly two and all other operators at least one.
+    SubPackets {
+        operator: Operator,
+        count: usize,
+    },
+    /// A packet nested deeper than `MAX_DEPTH`.
+    TooDeep,
+}
+
+/// A decoding error and the offset in bits from the start of the transmission
+/// where it occurred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitsError {
+    pub offset: usize,
+    pub kind: BitsErrorKind,
+}
+
+impl fmt::Display for BitsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            BitsErrorKind::InvalidHex(digit) => write!(f, "Invalid hex digit {digit:?}")?,
+            BitsErrorKind::UnexpectedEnd => write!(f, "Unexpected end of the transmission")?,
+            BitsErrorKind::InvalidOperator(id) => write!(f, "Invalid operator type id {id}")?,
+            BitsErrorKind::LiteralTooLong => write!(f, "Literal value exceeds 64 bits")?,
+            BitsErrorKind::LengthMismatch { expected, actual } => write!(
+                f,
+                "Sub-packets have {actual} bits, expected {expected}"
+            )?,
+            BitsErrorKind::SubPackets { operator, count } => {
+                write!(f, "{operator} can not have {count} sub-packets")?
+            }
+            BitsErrorKind::TooDeep => write!(f, "Packet nested deeper than {MAX_DEPTH}")?,
+        }
+        write!(f, " at bit {}", self.offset)
     }
 }
 
-fn parse_literal(mut input: Bits) -> IResult<Bits, (usize, u64)> {
-    let mut result = 0;
-    let mut size = 0;
+impl std::error::Error for BitsError {}
+
+/// The error of the nom parsers, it only knows the number of bits which were
+/// left, `parse_input` turns it into a `BitsError` with the offset.
+#[derive(Debug)]
+struct Failure {
+    remaining: usize,
+    kind: BitsErrorKind,
+}
+
+impl Failure {
+    fn new(input: Bits, kind: BitsErrorKind) -> nom::Err<Self> {
+        nom::Err::Failure(Self {
+            remaining: remaining(input),
+            kind,
+        })
+    }
+}
+
+impl ParseError<Bits<'_>> for Failure {
+    fn from_error_kind(input: Bits, _kind: ErrorKind) -> Self {
+        // The only nom parser used is `take`, which fails at the end of the input.
+        Self {
+            remaining: remaining(input),
+            kind: BitsErrorKind::UnexpectedEnd,
+        }
+    }
+
+    fn append(_input: Bits, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+type IResult<'a, T> = nom::IResult<Bits<'a>, T, Failure>;
+
+fn remaining((bytes, bit): Bits) -> usize {
+    bytes.len() * 8 - bit
+}
+
+fn hex_value(digit: u8, offset: usize) -> Result<u8, BitsError> {
+    match (digit as char).to_digit(16) {
+        Some(value) => Ok(value as u8),
+        None => Err(BitsError {
+            offset,
+            kind: BitsErrorKind::InvalidHex(digit as char),
+        }),
+    }
+}
+
+fn parse_literal(mut input: Bits) -> IResult<u64> {
+    let start = input;
+    let mut result: u64 = 0;
     loop {
         let (inp, ctrl): (Bits, u8) = take(1usize)(input)?;
         let (inp, number): (Bits, u64) = take(4usize)(inp)?;
 
+        if result.leading_zeros() < 4 {
+            return Err(Failure::new(start, BitsErrorKind::LiteralTooLong));
+        }
         result = result << 4 | number;
-        size += 5;
 
         input = inp;
         if ctrl == 0 {
-            break Ok((input, (size, result)));
+            break Ok((input, result));
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
-enum Operator {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
     Sum,
     Product,
     Minimum,
@@ -43,42 +142,76 @@ enum Operator {
 }
 
 impl Operator {
-    fn evaluate(&self, packets: &[Packet]) -> u64 {
-        let values = packets.iter().map(|packet| packet.evaluate());
+    /// Whether the operator can be applied to `count` sub-packets, comparisons
+    /// need exactly two and all other operators at least one.
+    fn accepts(&self, count: usize) -> bool {
         match self {
-            Self::Sum => values.sum(),
-            Self::Product => values.reduce(|accum, value| accum * value).unwrap(),
-            Self::Minimum => values.min().unwrap(),
-            Self::Maximum => values.max().unwrap(),
-            Self::GreaterThan => {
-                if packets[0].evaluate() > packets[1].evaluate() {
-                    1
-                } else {
-                    0
-                }
-            }
-            Self::LessThan => {
-                if packets[0].evaluate() < packets[1].evaluate() {
-                    1
-                } else {
-                    0
-                }
-            }
-            Self::EqualTo => {
-                if packets[0].evaluate() == packets[1].evaluate() {
-                    1
-                } else {
-                    0
-                }
-            }
+            Self::GreaterThan | Self::LessThan | Self::EqualTo => count == 2,
+            _ => count > 0,
         }
     }
+
+    fn apply<N: Number>(&self, values: &[N]) -> Result<N, EvalError> {
+        if !self.accepts(values.len()) {
+            return Err(EvalError::SubPackets {
+                operator: *self,
+                count: values.len(),
+            });
+        }
+
+        let overflow = || EvalError::Overflow { operator: *self };
+        let r = match self {
+            Self::Sum => values
+                .iter()
+                .try_fold(N::from_u64(0), |accum, value| accum.checked_add(value))
+                .ok_or_else(overflow)?,
+            Self::Product => values
+                .iter()
+                .try_fold(N::from_u64(1), |accum, value| accum.checked_mul(value))
+                .ok_or_else(overflow)?,
+            Self::Minimum => values.iter().min().unwrap().clone(),
+            Self::Maximum => values.iter().max().unwrap().clone(),
+            Self::GreaterThan => N::from_u64((values[0] > values[1]) as u64),
+            Self::LessThan => N::from_u64((values[0] < values[1]) as u64),
+            Self::EqualTo => N::from_u64((values[0] == values[1]) as u64),
+        };
+        Ok(r)
+    }
 }
 
-impl TryFrom<u32> for Operator {
-    type Error = anyhow::Error;
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Sum => "sum",
+            Self::Product => "product",
+            Self::Minimum => "min",
+            Self::Maximum => "max",
+            Self::GreaterThan => "gt",
+            Self::LessThan => "lt",
+            Self::EqualTo => "eq",
+        };
+        f.write_str(name)
+    }
+}
 
-    fn try_from(value: u32) -> Result<Self, Self::Error> {
+impl From<&Operator> for u64 {
+    fn from(operator: &Operator) -> Self {
+        match operator {
+            Operator::Sum => 0,
+            Operator::Product => 1,
+            Operator::Minimum => 2,
+            Operator::Maximum => 3,
+            Operator::GreaterThan => 5,
+            Operator::LessThan => 6,
+            Operator::EqualTo => 7,
+        }
+    }
+}
+
+impl TryFrom<u8> for Operator {
+    type Error = BitsErrorKind;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
         let r = match value {
             0 => Self::Sum,
             1 => Self::Product,
@@ -87,14 +220,209 @@ impl TryFrom<u32> for Operator {
             5 => Self::GreaterThan,
             6 => Self::LessThan,
             7 => Self::EqualTo,
-            _ => anyhow::bail!("invalid operator {}", value),
+            _ => return Err(BitsErrorKind::InvalidOperator(value)),
         };
         Ok(r)
     }
 }
 
-#[derive(Debug, PartialEq)]
-enum Packet {
+/// Why evaluating a packet failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    /// The value of the operator does not fit into the number type.
+    Overflow { operator: Operator },
+    /// The operator can not be applied to this many sub-packets.
+    ///
+    /// Parsed packets never have this error, the parser already rejects them,
+    /// but packets can also be built directly, e.g. to encode them.
+    SubPackets { operator: Operator, count: usize },
+    /// A packet nested deeper than `MAX_DEPTH`, like `SubPackets` only for
+    /// packets which were not parsed.
+    TooDeep,
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow { operator } => write!(f, "{operator} overflows"),
+            Self::SubPackets { operator, count } => {
+                write!(f, "{operator} can not have {count} sub-packets")
+            }
+            Self::TooDeep => write!(f, "Packet nested deeper than {MAX_DEPTH}"),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+/// The numbers packets are evaluated with.
+pub trait Number: Clone + Ord + fmt::Display {
+    fn from_u64(value: u64) -> Self;
+
+    /// The sum, `None` if it can not be represented.
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+
+    /// The product, `None` if it can not be represented.
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+}
+
+impl Number for u64 {
+    fn from_u64(value: u64) -> Self {
+        value
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        u64::checked_add(*self, *other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        u64::checked_mul(*self, *other)
+    }
+}
+
+/// Arbitrary precision unsigned integer, enough to evaluate any packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigUint {
+    /// Base 2^32 digits, least significant first, without leading zeros.
+    digits: Vec<u32>,
+}
+
+impl BigUint {
+    fn normalized(mut digits: Vec<u32>) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        Self { digits }
+    }
+}
+
+impl Number for BigUint {
+    fn from_u64(value: u64) -> Self {
+        Self::normalized(vec![value as u32, (value >> 32) as u32])
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        let len = self.digits.len().max(other.digits.len());
+        let mut digits = Vec::with_capacity(len + 1);
+        let mut carry = 0;
+        for i in 0..len {
+            let a = self.digits.get(i).copied().unwrap_or(0) as u64;
+            let b = other.digits.get(i).copied().unwrap_or(0) as u64;
+            let sum = a + b + carry;
+            digits.push(sum as u32);
+            carry = sum >> 32;
+        }
+        digits.push(carry as u32);
+        Some(Self::normalized(digits))
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let mut digits = vec![0u32; self.digits.len() + other.digits.len()];
+        for (i, &a) in self.digits.iter().enumerate() {
+            let mut carry = 0;
+            for (j, &b) in other.digits.iter().enumerate() {
+                let product = a as u64 * b as u64 + digits[i + j] as u64 + carry;
+                digits[i + j] = product as u32;
+                carry = product >> 32;
+            }
+            digits[i + other.digits.len()] = carry as u32;
+        }
+        Some(Self::normalized(digits))
+    }
+}
+
+impl PartialEq<u64> for BigUint {
+    fn eq(&self, other: &u64) -> bool {
+        *self == Self::from_u64(*other)
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.digits
+            .len()
+            .cmp(&other.digits.len())
+            .then_with(|| self.digits.iter().rev().cmp(other.digits.iter().rev()))
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Repeatedly divides by 10^9, the remainders are the decimal digits
+        // in groups of nine, least significant first.
+        let mut digits = self.digits.clone();
+        let mut groups = Vec::new();
+        while !digits.is_empty() {
+            let mut remainder = 0u64;
+            for digit in digits.iter_mut().rev() {
+                let value = remainder << 32 | *digit as u64;
+                *digit = (value / 1_000_000_000) as u32;
+                remainder = value % 1_000_000_000;
+            }
+            groups.push(remainder);
+            digits = Self::normalized(digits).digits;
+        }
+
+        match groups.split_last() {
+            None => write!(f, "0"),
+            Some((first, rest)) => {
+                write!(f, "{first}")?;
+                rest.iter().rev().try_for_each(|group| write!(f, "{group:09}"))
+            }
+        }
+    }
+}
+
+/// How an operator packet encodes the extent of its sub-packets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthType {
+    /// Length type id 0, the total length of the sub-packets in bits.
+    Bits,
+    /// Length type id 1, the number of sub-packets.
+    Packets,
+}
+
+/// Bits in transmission order, the last byte is padded with zeros.
+#[derive(Debug, Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    /// Appends the lowest `count` bits of `value`, most significant bit first.
+    fn push(&mut self, value: u64, count: usize) {
+        for i in (0..count).rev() {
+            self.push_bit(value >> i & 1 == 1);
+        }
+    }
+
+    fn push_bit(&mut self, bit: bool) {
+        if self.len.is_multiple_of(8) {
+            self.bytes.push(0);
+        }
+        if bit {
+            *self.bytes.last_mut().unwrap() |= 0x80 >> (self.len % 8);
+        }
+        self.len += 1;
+    }
+
+    fn append(&mut self, other: &BitWriter) {
+        for i in 0..other.len {
+            self.push_bit(other.bytes[i / 8] & 0x80 >> (i % 8) != 0);
+        }
+    }
+}
+
+/// A BITS packet, either a literal value or an operator on its sub-packets.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Packet {
     Literal {
         version: u8,
         value: u64,
@@ -123,95 +451,311 @@ impl Packet {
         }
     }
 
-    fn evaluate(&self) -> u64 {
+    fn evaluate<N: Number>(&self) -> Result<N, EvalError> {
+        self.evaluate_with(&mut |_, _, _, _| {})
+    }
+
+    /// Evaluates the packet and calls `trace` for every operator once its value
+    /// is known, with its depth in the tree and the values of its sub-packets.
+    fn evaluate_with<N: Number>(
+        &self,
+        trace: &mut impl FnMut(&Operator, usize, &[N], &N),
+    ) -> Result<N, EvalError> {
+        self.evaluate_at(0, trace)
+    }
+
+    fn evaluate_at<N: Number>(
+        &self,
+        depth: usize,
+        trace: &mut impl FnMut(&Operator, usize, &[N], &N),
+    ) -> Result<N, EvalError> {
+        if depth > MAX_DEPTH {
+            return Err(EvalError::TooDeep);
+        }
         match self {
-            Self::Literal { value, .. } => *value,
+            Self::Literal { value, .. } => Ok(N::from_u64(*value)),
             Self::Operator {
                 operator, packets, ..
-            } => operator.evaluate(packets),
+            } => {
+                let values = packets
+                    .iter()
+                    .map(|packet| packet.evaluate_at(depth + 1, trace))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let value = operator.apply(&values)?;
+                trace(operator, depth, &values, &value);
+                Ok(value)
+            }
+        }
+    }
+
+    /// Evaluates the packet and passes every step to `step`, innermost operators
+    /// first, e.g. `product(6, 9) = 54`.
+    ///
+    /// The steps before a failing operator are still passed on.
+    fn trace<N: Number>(&self, mut step: impl FnMut(String)) -> Result<N, EvalError> {
+        self.evaluate_with(&mut |operator, depth, values: &[N], value| {
+            let values = values.iter().map(N::to_string).collect::<Vec<_>>();
+            let indent = "  ".repeat(depth);
+            step(format!("{indent}{operator}({}) = {value}", values.join(", ")));
+        })
+    }
+
+    fn write_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let indent = "    ".repeat(depth);
+        match self {
+            Self::Literal { version, value } => write!(f, "{indent}{value} [v{version}]"),
+            Self::Operator {
+                version,
+                operator,
+                packets,
+            } => {
+                writeln!(f, "{indent}{operator} [v{version}](")?;
+                for packet in packets {
+                    packet.write_indented(f, depth + 1)?;
+                    writeln!(f, ",")?;
+                }
+                write!(f, "{indent})")
+            }
         }
     }
 }
 
-fn parse_packet(input: Bits) -> IResult<Bits, (usize, Packet)> {
-    let (input, version) = take(3usize)(input)?;
-    let (input, type_id) = take(3usize)(input)?;
-
-    match type_id {
-        4 => {
-            let (input, (size, value)) = parse_literal(input)?;
-            Ok((input, (size + 6, Packet::Literal { version, value })))
-        }
-        type_id => {
-            let operator = type_id.try_into().unwrap();
-            let (input, length_type_id): (Bits, usize) = take(1usize)(input)?;
-            let length_size = 15usize - (length_type_id << 2);
-            let (mut input, length): (Bits, usize) = take(length_size)(input)?;
-            let mut packets = vec![];
-            let mut sub_size = 0;
-            for i in 0.. {
-                let (inp, (size, packet)) = parse_packet(input)?;
-                sub_size += size;
-                input = inp;
-                packets.push(packet);
-
-                if length_type_id == 0 && sub_size >= length {
-                    assert_eq!(sub_size, length);
-                    break;
-                } else if length_type_id == 1 && i + 1 >= length {
-                    break;
+/// Renders the packet as an expression, e.g. `sum(product(6, 9), min(7, 8))`.
+///
+/// The alternate form `{:#}` puts every packet on its own line, indented by
+/// its depth and annotated with its version.
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return self.write_indented(f, 0);
+        }
+
+        match self {
+            Self::Literal { value, .. } => write!(f, "{value}"),
+            Self::Operator {
+                operator, packets, ..
+            } => {
+                write!(f, "{operator}(")?;
+                for (i, packet) in packets.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{packet}")?;
                 }
+                write!(f, ")")
             }
-            let size = 6 + 1 + length_size + sub_size;
-            Ok((
-                input,
-                (
-                    size,
-                    Packet::Operator {
-                        version,
-                        operator,
-                        packets,
-                    },
-                ),
-            ))
         }
     }
 }
 
-fn parse_input(input: &[u8]) -> IResult<&[u8], Packet> {
-    let (_, packet) = parse_packet((input, 0)).unwrap();
-    Ok((input, packet.1))
+impl Packet {
+    /// Encodes the packet, operators use the total length of their sub-packets in bits.
+    pub fn encode(&self) -> Vec<u8> {
+        self.encode_with(LengthType::Bits)
+    }
+
+    /// Encodes the packet with `length_type` for every operator.
+    ///
+    /// Panics if the version does not fit into 3 bits or the sub-packets of an
+    /// operator do not fit into the length field.
+    pub fn encode_with(&self, length_type: LengthType) -> Vec<u8> {
+        let mut writer = BitWriter::default();
+        self.write(&mut writer, length_type);
+        writer.bytes
+    }
+
+    /// Encodes the packet as upper case hex, the format of the puzzle input.
+    pub fn to_hex(&self, length_type: LengthType) -> String {
+        self.encode_with(length_type)
+            .iter()
+            .map(|byte| format!("{byte:02X}"))
+            .collect()
+    }
+
+    fn write(&self, writer: &mut BitWriter, length_type: LengthType) {
+        assert!(self.version() < 8, "version {} exceeds 3 bits", self.version());
+        writer.push(self.version() as u64, 3);
+
+        match self {
+            Self::Literal { value, .. } => {
+                writer.push(4, 3);
+                let groups = (64 - value.leading_zeros() as usize).div_ceil(4).max(1);
+                for group in (0..groups).rev() {
+                    writer.push_bit(group > 0);
+                    writer.push(value >> (group * 4), 4);
+                }
+            }
+            Self::Operator {
+                operator, packets, ..
+            } => {
+                writer.push(operator.into(), 3);
+
+                let mut sub_packets = BitWriter::default();
+                for packet in packets {
+                    packet.write(&mut sub_packets, length_type);
+                }
+
+                let (id, length, size) = match length_type {
+                    LengthType::Bits => (0, sub_packets.len, 15),
+                    LengthType::Packets => (1, packets.len(), 11),
+                };
+                assert!(length < 1 << size, "{length} does not fit into {size} bits");
+                writer.push(id, 1);
+                writer.push(length as u64, size);
+                writer.append(&sub_packets);
+            }
+        }
+    }
+}
+
+fn parse_packet(input: Bits, depth: usize) -> IResult<Packet> {
+    let start = input;
+    if depth > MAX_DEPTH {
+        return Err(Failure::new(start, BitsErrorKind::TooDeep));
+    }
+    let (input, version) = take(3usize)(input)?;
+    let (input, type_id): (Bits, u8) = take(3usize)(input)?;
+
+    if type_id == 4 {
+        let (input, value) = parse_literal(input)?;
+        return Ok((input, Packet::Literal { version, value }));
+    }
+
+    let operator = Operator::try_from(type_id).map_err(|kind| Failure::new(start, kind))?;
+    let (input, length_type_id): (Bits, u8) = take(1usize)(input)?;
+    let length_size = if length_type_id == 0 { 15usize } else { 11 };
+    let (mut input, length): (Bits, usize) = take(length_size)(input)?;
+
+    let mut packets = vec![];
+    if length_type_id == 0 {
+        let sub_packets = remaining(input);
+        let end = sub_packets
+            .checked_sub(length)
+            .ok_or_else(|| Failure::new(input, BitsErrorKind::UnexpectedEnd))?;
+        while remaining(input) > end {
+            let (inp, packet) = parse_packet(input, depth + 1)?;
+            input = inp;
+            packets.push(packet);
+        }
+        if remaining(input) != end {
+            let actual = sub_packets - remaining(input);
+            let kind = BitsErrorKind::LengthMismatch {
+                expected: length,
+                actual,
+            };
+            return Err(Failure::new(start, kind));
+        }
+    } else {
+        while packets.len() < length {
+            let (inp, packet) = parse_packet(input, depth + 1)?;
+            input = inp;
+            packets.push(packet);
+        }
+    }
+
+    let count = packets.len();
+    if !operator.accepts(count) {
+        return Err(Failure::new(
+            start,
+            BitsErrorKind::SubPackets { operator, count },
+        ));
+    }
+
+    Ok((
+        input,
+        Packet::Operator {
+            version,
+            operator,
+            packets,
+        },
+    ))
+}
+
+/// Parses the outermost packet of the transmission, the padding after it is ignored.
+pub fn parse_input(input: &[u8]) -> Result<Packet, BitsError> {
+    match parse_packet((input, 0), 0) {
+        Ok((_, packet)) => Ok(packet),
+        Err(nom::Err::Error(failure) | nom::Err::Failure(failure)) => Err(BitsError {
+            offset: input.len() * 8 - failure.remaining,
+            kind: failure.kind,
+        }),
+        Err(nom::Err::Incomplete(_)) => unreachable!("complete parsers never need more input"),
+    }
+}
+
+/// Converts hex digits into the bytes of the transmission, surrounding whitespace
+/// like the trailing newline is ignored and both cases are accepted.
+pub fn decode(hex: &[u8]) -> Result<Vec<u8>, BitsError> {
+    hex.trim_ascii()
+        .chunks(2)
+        .enumerate()
+        .map(|(i, pair)| {
+            let high = hex_value(pair[0], i * 8)?;
+            // An odd number of digits is padded like the transmission itself.
+            let low = match pair.get(1) {
+                Some(&digit) => hex_value(digit, i * 8 + 4)?,
+                None => 0,
+            };
+            Ok(high << 4 | low)
+        })
+        .collect()
 }
 
-fn read(reader: Input) -> Vec<u8> {
-    reader
-        .bytes()
-        .map(|b| b.unwrap())
-        .tuples()
-        .map(|(a, b)| hex_value(a) << 4 | hex_value(b))
-        .collect::<Vec<_>>()
+fn read(mut reader: Input) -> anyhow::Result<Packet> {
+    let mut hex = Vec::new();
+    reader.read_to_end(&mut hex)?;
+    Ok(parse_input(&decode(&hex)?)?)
 }
 
 pub fn part1(reader: Input) -> anyhow::Result<u64> {
-    Ok(parse_input(&read(reader)).unwrap().1.sum_version() as u64)
+    Ok(read(reader)?.sum_version() as u64)
 }
 
-pub fn part2(reader: Input) -> anyhow::Result<u64> {
-    Ok(parse_input(&read(reader)).unwrap().1.evaluate())
+/// The result of part 2, `u64` which errors on overflow unless the `bigint`
+/// feature switches to arbitrary precision. Literals are `u64` either way.
+#[cfg(not(feature = "bigint"))]
+type Value = u64;
+#[cfg(feature = "bigint")]
+type Value = BigUint;
+
+pub fn part2(reader: Input) -> anyhow::Result<Value> {
+    let packet = read(reader)?;
+    if cli::visualize() {
+        eprintln!("{packet:#}");
+        return Ok(packet.trace(|step| eprintln!("{step}"))?);
+    }
+    Ok(packet.evaluate()?)
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    operator_packets: "8A004A801A8002F478" => { part1: 16 }
+    nested_operators: "620080001611562C8802118E34" => { part1: 12 }
+    length_type_0: "C0015000016115A2E0802F182340" => { part1: 23 }
+    deeply_nested: "A0016C880162017C3686B18A3D4780" => { part1: 31 }
+    sum: "C200B40A82" => { part2: 3 }
+    product: "04005AC33890" => { part2: 54 }
+    minimum: "880086C3E88112" => { part2: 7 }
+    maximum: "CE00C43D881120" => { part2: 9 }
+    less_than: "D8005AC2A8F0" => { part2: 1 }
+    greater_than: "F600BC2D8F" => { part2: 0 }
+    equal_to: "9C005AC2F8F0" => { part2: 0 }
+    sum_equals_product: "9C0141080250320F1802104A08" => { part2: 1 }
+    lowercase: "c200b40a82\n" => { part2: 3 }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+
+    fn parse_hex(hex: &[u8]) -> Packet {
+        parse_input(&decode(hex).unwrap()).unwrap()
+    }
 
     #[test]
     fn test_parse_literal() {
         assert_eq!(
-            parse_input(b"D2FE28").unwrap().1,
+            parse_hex(b"D2FE28"),
             Packet::Literal {
                 version: 6,
                 value: 2021
@@ -222,7 +766,7 @@ mod tests {
     #[test]
     fn test_parse_operator() {
         assert_eq!(
-            parse_input(b"38006F45291200").unwrap().1,
+            parse_hex(b"38006F45291200"),
             Packet::Operator {
                 version: 1,
                 operator: Operator::LessThan,
@@ -241,49 +785,214 @@ mod tests {
     }
 
     #[test]
-    fn test_sum() {
+    fn test_encode() {
+        let packet = parse_hex(b"D2FE28");
+        assert_eq!(packet.to_hex(LengthType::Bits), "D2FE28");
+
+        // The examples use length type id 0 and 1 respectively.
         assert_eq!(
-            parse_input(b"8A004A801A8002F478").unwrap().1.sum_version(),
-            16
+            parse_hex(b"38006F45291200").to_hex(LengthType::Bits),
+            "38006F45291200"
         );
         assert_eq!(
-            parse_input(b"620080001611562C8802118E34")
-                .unwrap()
-                .1
-                .sum_version(),
-            12
+            parse_hex(b"EE00D40C823060").to_hex(LengthType::Packets),
+            "EE00D40C823060"
         );
+    }
+
+    #[test]
+    fn test_display() {
+        let packet = parse_hex(b"9C0141080250320F1802104A08");
+        assert_eq!(packet.to_string(), "eq(sum(1, 3), product(2, 2))");
         assert_eq!(
-            parse_input(b"C0015000016115A2E0802F182340")
-                .unwrap()
-                .1
-                .sum_version(),
-            23
+            format!("{packet:#}"),
+            "\
+eq [v4](
+    sum [v2](
+        1 [v2],
+        3 [v4],
+    ),
+    product [v6](
+        2 [v0],
+        2 [v2],
+    ),
+)"
         );
+    }
+
+    #[test]
+    fn test_trace() {
+        let packet = parse_hex(b"9C0141080250320F1802104A08");
+        let mut steps = Vec::new();
+        assert_eq!(packet.trace(|step| steps.push(step)), Ok(1u64));
+        assert_eq!(
+            steps,
+            vec!["  sum(1, 3) = 4", "  product(2, 2) = 4", "eq(4, 4) = 1"]
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        let literal = |value| Packet::Literal { version: 0, value };
+        let operator = |operator, packets| Packet::Operator {
+            version: 0,
+            operator,
+            packets,
+        };
+
+        let product = operator(Operator::Product, vec![literal(u64::MAX), literal(2)]);
+        let overflow = Err(EvalError::Overflow {
+            operator: Operator::Product,
+        });
+        assert_eq!(product.evaluate::<u64>(), overflow);
         assert_eq!(
-            parse_input(b"A0016C880162017C3686B18A3D4780")
-                .unwrap()
-                .1
-                .sum_version(),
-            31
+            product.evaluate::<BigUint>().unwrap().to_string(),
+            "36893488147419103230"
         );
+
+        // Comparisons of values beyond 64 bits are exact.
+        let sum = operator(Operator::Sum, vec![literal(u64::MAX), literal(1)]);
+        let packet = operator(Operator::GreaterThan, vec![product, sum]);
+        assert_eq!(packet.evaluate::<BigUint>(), Ok(BigUint::from_u64(1)));
+
+        let packet = operator(Operator::LessThan, vec![literal(1)]);
+        let err = packet.evaluate::<u64>().unwrap_err();
+        assert_eq!(err.to_string(), "lt can not have 1 sub-packets");
+    }
+
+    #[test]
+    fn test_big_uint() {
+        let big = BigUint::from_u64(1 << 63);
+        let square = big.checked_mul(&big).unwrap();
+        assert_eq!(square.to_string(), "85070591730234615865843651857942052864");
+        let sum = square.checked_add(&BigUint::from_u64(u64::MAX)).unwrap();
+        assert_eq!(sum.to_string(), "85070591730234615884290395931651604479");
+        assert!(sum > square && square > big);
+        assert_eq!(BigUint::from_u64(0).to_string(), "0");
+        assert_eq!(BigUint::from_u64(1_000_000_000).to_string(), "1000000000");
+    }
+
+    fn parse_error(hex: &[u8]) -> BitsError {
+        decode(hex).and_then(|bytes| parse_input(&bytes)).unwrap_err()
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = parse_error(b"D2FG28");
+        assert_eq!(err.kind, BitsErrorKind::InvalidHex('G'));
+        assert_eq!(err.to_string(), "Invalid hex digit 'G' at bit 12");
+
+        let err = parse_error(b"D2FE");
+        assert_eq!((err.offset, err.kind), (16, BitsErrorKind::UnexpectedEnd));
+
+        // A sum announcing 10 bits of sub-packets followed by an 11 bit literal.
+        let mut writer = BitWriter::default();
+        writer.push(0, 3 + 3 + 1);
+        writer.push(10, 15);
+        writer.push(0b000_100, 6);
+        writer.push(0b0_0001, 5);
+        let err = parse_input(&writer.bytes).unwrap_err();
+        let kind = BitsErrorKind::LengthMismatch {
+            expected: 10,
+            actual: 11,
+        };
+        assert_eq!((err.offset, err.kind), (0, kind));
+
+        // A literal with 17 groups of 4 bits.
+        let mut writer = BitWriter::default();
+        writer.push(0b000_100, 6);
+        for group in (0..17).rev() {
+            writer.push_bit(group > 0);
+            writer.push(0xF, 4);
+        }
+        let err = parse_input(&writer.bytes).unwrap_err();
+        assert_eq!((err.offset, err.kind), (6, BitsErrorKind::LiteralTooLong));
+
+        let packet = Packet::Operator {
+            version: 1,
+            operator: Operator::GreaterThan,
+            packets: vec![Packet::Literal {
+                version: 2,
+                value: 3,
+            }],
+        };
+        let err = parse_input(&packet.encode()).unwrap_err();
+        let kind = BitsErrorKind::SubPackets {
+            operator: Operator::GreaterThan,
+            count: 1,
+        };
+        assert_eq!((err.offset, err.kind), (0, kind));
     }
 
     #[test]
-    fn test_evaluate() {
-        assert_eq!(parse_input(b"C200B40A82").unwrap().1.evaluate(), 3);
-        assert_eq!(parse_input(b"04005AC33890").unwrap().1.evaluate(), 54);
-        assert_eq!(parse_input(b"880086C3E88112").unwrap().1.evaluate(), 7);
-        assert_eq!(parse_input(b"CE00C43D881120").unwrap().1.evaluate(), 9);
-        assert_eq!(parse_input(b"D8005AC2A8F0").unwrap().1.evaluate(), 1);
-        assert_eq!(parse_input(b"F600BC2D8F").unwrap().1.evaluate(), 0);
-        assert_eq!(parse_input(b"9C005AC2F8F0").unwrap().1.evaluate(), 0);
+    fn test_too_deep() {
+        // Sums with a single sub-packet nested far deeper than the stack allows
+        // to recurse, every operator header has 18 bits.
+        let mut writer = BitWriter::default();
+        for _ in 0..100_000 {
+            writer.push(0, 3 + 3);
+            writer.push(1, 1);
+            writer.push(1, 11);
+        }
+        writer.push(0b000_100, 6);
+        writer.push(0b0_0001, 5);
+        let err = parse_input(&writer.bytes).unwrap_err();
         assert_eq!(
-            parse_input(b"9C0141080250320F1802104A08")
-                .unwrap()
-                .1
-                .evaluate(),
-            1
+            (err.offset, err.kind),
+            ((MAX_DEPTH + 1) * 18, BitsErrorKind::TooDeep)
         );
+
+        let mut packet = Packet::Literal {
+            version: 0,
+            value: 1,
+        };
+        for _ in 0..=MAX_DEPTH {
+            packet = Packet::Operator {
+                version: 0,
+                operator: Operator::Sum,
+                packets: vec![packet],
+            };
+        }
+        assert_eq!(packet.evaluate::<u64>(), Err(EvalError::TooDeep));
+    }
+
+    fn packet() -> impl Strategy<Value = Packet> {
+        let literal = (0..8u8, any::<u64>()).prop_map(|(version, value)| Packet::Literal { version, value });
+        literal.prop_recursive(4, 32, 4, |inner| {
+            let operator = prop_oneof![
+                Just(Operator::Sum),
+                Just(Operator::Product),
+                Just(Operator::Minimum),
+                Just(Operator::Maximum),
+                Just(Operator::GreaterThan),
+                Just(Operator::LessThan),
+                Just(Operator::EqualTo),
+            ];
+            (0..8u8, operator, prop::collection::vec(inner, 1..4)).prop_map(
+                |(version, operator, mut packets)| {
+                    // Comparisons always have exactly two sub-packets.
+                    if u64::from(&operator) >= 5 {
+                        packets.resize(2, Packet::Literal { version: 0, value: 0 });
+                    }
+                    Packet::Operator {
+                        version,
+                        operator,
+                        packets,
+                    }
+                },
+            )
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_encode_roundtrip(packet in packet()) {
+            for length_type in [LengthType::Bits, LengthType::Packets] {
+                let parsed = parse_input(&packet.encode_with(length_type)).unwrap();
+                prop_assert_eq!(&parsed, &packet);
+            }
+            prop_assert_eq!(parse_hex(packet.to_hex(LengthType::Packets).as_bytes()), packet.clone());
+            prop_assert_eq!(parse_input(&packet.encode()).unwrap(), packet);
+        }
     }
 }
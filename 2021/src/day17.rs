@@ -1,4 +1,4 @@
-use aoc2021::Input;
+use crate::Input;
 use serde_scan::scan;
 use std::io::BufRead;
 
@@ -100,8 +100,8 @@ pub fn part2(reader: Input) -> anyhow::Result<i64> {
     Ok(result.1)
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "target area: x=20..30, y=-10..-5" => { part1: 45, part2: 112 }
 }
 
 #[cfg(test)]
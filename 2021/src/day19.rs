@@ -1,5 +1,5 @@
 #![allow(dead_code)]
-use aoc2021::Input;
+use crate::Input;
 use itertools::Itertools;
 use std::{
     collections::{hash_map::Entry, HashMap, HashSet},
@@ -178,7 +178,7 @@ impl Vec3 {
     }
 
     fn manhattan(&self) -> u32 {
-        self.x.abs() as u32 + self.y.abs() as u32 + self.z.abs() as u32
+        self.x.unsigned_abs() + self.y.unsigned_abs() + self.z.unsigned_abs()
     }
 
     fn abs(&self) -> Self {
@@ -521,7 +521,7 @@ pub fn part1(reader: Input) -> anyhow::Result<u32> {
 }
 
 fn transform2(
-    points: &mut Vec<Vec3>,
+    points: &mut [Vec3],
     from: &str,
     to: &str,
     transitions: &[Vec<(&str, Operation)>],
@@ -611,10 +611,6 @@ pub fn part2(_reader: Input) -> anyhow::Result<u32> {
     Ok(0)
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,7 +1,7 @@
-use aoc2021::grid::Grid;
-use aoc2021::Input;
-use itertools::Itertools;
-use std::io::BufRead;
+use crate::automaton::{Automaton, Neighborhood};
+use crate::grid::Grid;
+use crate::Input;
+use anyhow::Context;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 enum Pixel {
@@ -18,12 +18,14 @@ impl Pixel {
     }
 }
 
-impl From<u8> for Pixel {
-    fn from(data: u8) -> Self {
+impl TryFrom<u8> for Pixel {
+    type Error = anyhow::Error;
+
+    fn try_from(data: u8) -> Result<Self, Self::Error> {
         match data {
-            b'#' => Pixel::Light,
-            b'.' => Pixel::Dark,
-            _ => unreachable!(),
+            b'#' => Ok(Pixel::Light),
+            b'.' => Ok(Pixel::Dark),
+            _ => anyhow::bail!("Invalid pixel"),
         }
     }
 }
@@ -37,47 +39,30 @@ impl std::fmt::Display for Pixel {
     }
 }
 
-fn read_table(reader: &mut Input) -> Vec<Pixel> {
-    reader
-        .lines()
+fn read_table(reader: &mut Input) -> anyhow::Result<Vec<Pixel>> {
+    let line = reader
+        .lines_parsed::<String>()
         .next()
-        .unwrap()
-        .unwrap()
-        .as_bytes()
-        .iter()
-        .copied()
-        .map(Into::into)
-        .collect()
+        .context("Missing enhancement algorithm")??;
+    line.bytes().map(Pixel::try_from).collect()
 }
 
-fn enhance(mut image: Grid<Pixel>, table: &[Pixel], iterations: usize) -> usize {
-    let mut def = Pixel::Dark;
-    for _ in 0..iterations {
-        let data = image
-            .kernel_ext_3x3(def)
-            .map(|(_, k)| {
-                let index = k
-                    .map(|pixel| pixel.as_bit() as u16)
-                    .into_iter()
-                    .reduce(|a, b| a << 1 | b)
-                    .unwrap();
-                table[index as usize]
-            })
-            .collect_vec();
-
-        image = Grid::new(data, image.width() + 2, image.height() + 2);
-
-        if table[0] != Pixel::Dark {
-            def = match def {
-                // all light pixels -> is 0b111_111_111 -> swap to the pixel at that index
-                Pixel::Light => table[0b111111111],
-                // all dark pixels -> is 0b000_000_000 -> swap to the pixel at that index
-                Pixel::Dark => table[0],
-            }
-        }
-    }
+fn enhance(image: Grid<Pixel>, table: &[Pixel], iterations: usize) -> usize {
+    // The infinite background flips with every step if the table maps an all
+    // dark neighbourhood to a light pixel.
+    let mut image = Automaton::infinite(image, 1, Pixel::Dark);
+    image.run(
+        &mut |pixels: &Neighborhood<'_, Pixel>| {
+            let index = pixels
+                .iter()
+                .fold(0, |index, pixel| index << 1 | pixel.as_bit() as usize);
+            table[index]
+        },
+        iterations,
+    );
 
     image
+        .grid()
         .data()
         .iter()
         .filter(|pixel| matches!(pixel, Pixel::Light))
@@ -85,19 +70,27 @@ fn enhance(mut image: Grid<Pixel>, table: &[Pixel], iterations: usize) -> usize
 }
 
 pub fn part1(mut reader: Input) -> anyhow::Result<usize> {
-    let table = read_table(&mut reader);
-    let image = Grid::read(&mut reader);
+    let table = read_table(&mut reader)?;
+    let image = reader.grid()?;
 
     Ok(enhance(image, &table, 2))
 }
 
 pub fn part2(mut reader: Input) -> anyhow::Result<usize> {
-    let table = read_table(&mut reader);
-    let image = Grid::read(&mut reader);
+    let table = read_table(&mut reader)?;
+    let image = reader.grid()?;
 
     Ok(enhance(image, &table, 50))
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#
+
+#..#.
+#....
+##..#
+..#..
+..###
+" => { part1: 35, part2: 3351 }
 }
@@ -1,4 +1,4 @@
-use aoc2021::Input;
+use crate::Input;
 use std::collections::HashMap;
 use std::io::BufRead;
 
@@ -136,6 +136,9 @@ pub fn part2(reader: Input) -> anyhow::Result<u64> {
     Ok(result.0.max(result.1))
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+Player 1 starting position: 4
+Player 2 starting position: 8
+" => { part1: 739785, part2: 444356092776315 }
 }
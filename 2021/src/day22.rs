@@ -1,4 +1,4 @@
-use aoc2021::Input;
+use crate::{cli, Input};
 use serde_scan::scan;
 use std::io::BufRead;
 
@@ -134,7 +134,7 @@ fn restart_reactor(input: impl Iterator<Item = (bool, Cuboid)>) -> u64 {
 }
 
 pub fn part1(reader: Input) -> anyhow::Result<u64> {
-    let input = reader
+    let input: Vec<_> = reader
         .lines()
         .map(|line| line.unwrap())
         .map(|line| parse(&line))
@@ -145,20 +145,29 @@ pub fn part1(reader: Input) -> anyhow::Result<u64> {
                 || cuboid.max.y > 51
                 || cuboid.min.z < -50
                 || cuboid.max.z > 51)
-        });
-    Ok(restart_reactor(input))
+        })
+        .collect();
+    cli::parsed();
+    Ok(restart_reactor(input.into_iter()))
 }
 
 pub fn part2(reader: Input) -> anyhow::Result<u64> {
-    let input = reader
+    let input: Vec<_> = reader
         .lines()
         .map(|line| line.unwrap())
-        .map(|line| parse(&line));
-    Ok(restart_reactor(input))
+        .map(|line| parse(&line))
+        .collect();
+    cli::parsed();
+    Ok(restart_reactor(input.into_iter()))
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    small: "
+on x=10..12,y=10..12,z=10..12
+on x=11..13,y=11..13,z=11..13
+off x=9..11,y=9..11,z=9..11
+on x=10..10,y=10..10,z=10..10
+" => { part1: 39, part2: 39 }
 }
 
 #[cfg(test)]
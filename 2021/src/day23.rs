@@ -1,9 +1,6 @@
-use aoc2021::Input;
-use std::{
-    collections::BinaryHeap,
-    fmt::Display,
-    io::BufRead,
-};
+use crate::{cli, search, Input};
+use anyhow::Context;
+use std::{fmt::Display, io::BufRead};
 
 type Cost = u64;
 
@@ -131,7 +128,7 @@ impl Connections {
     // }
 
     fn int_move(&self, from_tile: usize, to_tile: usize, target_is_tile: bool) -> Cost {
-        if target_is_tile && to_tile >= 2 && to_tile <= self.0.len() - 2 && to_tile % 2 == 0 {
+        if target_is_tile && to_tile >= 2 && to_tile <= self.0.len() - 2 && to_tile.is_multiple_of(2) {
             // to_tile is not accessible (directly infront of a room)
             return 0;
         }
@@ -299,53 +296,25 @@ fn parse<const ROOM_SIZE: usize>(reader: Input) -> Cave<ROOM_SIZE> {
     Cave { rooms, connections }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-struct Element<T: Eq> {
-    cost: Cost,
-    item: T,
-}
-
-impl<T: Eq> Element<T> {
-    fn new(cost: Cost, item: T) -> Self {
-        Self { cost, item }
-    }
-}
-
-impl<T: Eq> PartialOrd for Element<T> {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl<T: Eq> Ord for Element<T> {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.cost.cmp(&self.cost)
-    }
-}
-
-fn solve<const ROOM_SIZE: usize>(cave: Cave<ROOM_SIZE>) -> Cost {
-    let mut candidates = BinaryHeap::with_capacity(128);
-    candidates.push(Element::new(0, cave));
-
-    let mut seen = fxhash::FxHashSet::default();
-
-    while let Some(element) = candidates.pop() {
-        if element.item.is_done() {
-            return element.cost;
-        }
-
-        let moves = element.item.generate_moves().unwrap();
-        for (cost, new_cave) in moves {
-            if seen.insert(new_cave) {
-                candidates.push(Element::new(element.cost + cost, new_cave));
-            }
-        }
-    }
-    0
+fn solve<const ROOM_SIZE: usize>(cave: Cave<ROOM_SIZE>) -> Option<Cost> {
+    let path = search::dijkstra(
+        cave,
+        |cave| {
+            cave.generate_moves()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(cost, cave)| (cave, cost))
+        },
+        |cave| cave.is_done(),
+    )?;
+
+    Some(path.cost)
 }
 
 pub fn part1(reader: Input) -> anyhow::Result<Cost> {
-    Ok(solve(parse::<2>(reader)))
+    let cave = parse::<2>(reader);
+    cli::parsed();
+    solve(cave).context("No solution found")
 }
 
 pub fn part2(reader: Input) -> anyhow::Result<Cost> {
@@ -373,11 +342,18 @@ pub fn part2(reader: Input) -> anyhow::Result<Cost> {
         connections: small_cave.connections,
         rooms,
     };
+    cli::parsed();
 
     // let cave = parse::<4>(reader);
-    Ok(solve(cave))
+    solve(cave).context("No solution found")
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+#############
+#...........#
+###B#C#B#D###
+  #A#D#C#A#
+  #########
+" => { part1: 12521, part2: 44169 }
 }
@@ -1,9 +1,9 @@
+use crate::Input;
 use anyhow::Context;
-use aoc2021::Input;
-use itertools::Itertools;
+use std::collections::HashSet;
 use std::io::BufRead;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum Register {
     W,
     X,
@@ -30,36 +30,15 @@ trait EqualOp {
     fn equal(self, rhs: Self) -> Self::Output;
 }
 
-impl EqualOp for i32 {
+impl EqualOp for i64 {
     type Output = Self;
     fn equal(self, rhs: Self) -> Self::Output {
-        (self == rhs) as i32
+        (self == rhs) as i64
     }
 }
 
 type Registers<T> = [T; 4];
 
-#[derive(Debug, Copy, Clone)]
-enum Operation {
-    Add,
-    Mul,
-    Div,
-    Mod,
-    Eql,
-}
-
-impl std::fmt::Display for Operation {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Add => write!(f, "+"),
-            Self::Mul => write!(f, "*"),
-            Self::Div => write!(f, "/"),
-            Self::Mod => write!(f, "%"),
-            Self::Eql => write!(f, "=="),
-        }
-    }
-}
-
 #[derive(Debug)]
 enum Instruction {
     Inp(Register),
@@ -98,6 +77,74 @@ impl Instruction {
             Self::Eql(register, value) => op!(alu, register, value, |a: T, b| a.equal(b)),
         }
     }
+
+    /// Whether the ALU crashes on the instruction, dividing by zero, a modulo
+    /// of a negative number or by a non-positive number, or a result which
+    /// overflows the registers.
+    fn crashes(&self, alu: &Alu<i64>) -> bool {
+        match self {
+            Self::Add(register, value) => {
+                let (a, b) = (*alu.read(*register), value.resolve(alu));
+                a.checked_add(b).is_none()
+            }
+            Self::Mul(register, value) => {
+                let (a, b) = (*alu.read(*register), value.resolve(alu));
+                a.checked_mul(b).is_none()
+            }
+            Self::Div(register, value) => {
+                let (a, b) = (*alu.read(*register), value.resolve(alu));
+                a.checked_div(b).is_none()
+            }
+            Self::Mod(register, value) => *alu.read(*register) < 0 || value.resolve(alu) <= 0,
+            _ => false,
+        }
+    }
+
+    /// The registers `crashes` looks at, they matter even if the result is
+    /// never read.
+    fn checks(&self) -> [Option<Register>; 2] {
+        match self {
+            Self::Inp(_) | Self::Eql(_, _) => [None, None],
+            // Multiplying by zero never overflows.
+            Self::Mul(_, Value::Constant(0)) => [None, None],
+            Self::Add(register, value)
+            | Self::Mul(register, value)
+            | Self::Div(register, value)
+            | Self::Mod(register, value) => match value {
+                Value::Register(other) => [Some(*register), Some(*other)],
+                Value::Constant(_) => [Some(*register), None],
+            },
+        }
+    }
+
+    /// The register the instruction writes to.
+    fn target(&self) -> Register {
+        match self {
+            Self::Inp(register)
+            | Self::Add(register, _)
+            | Self::Mul(register, _)
+            | Self::Div(register, _)
+            | Self::Mod(register, _)
+            | Self::Eql(register, _) => *register,
+        }
+    }
+
+    /// The registers the result of the instruction depends on.
+    fn reads(&self) -> [Option<Register>; 2] {
+        match self {
+            Self::Inp(_) => [None, None],
+            // Always zero, whatever the register was before.
+            Self::Mul(_, Value::Constant(0)) => [None, None],
+            Self::Add(register, value)
+            | Self::Mul(register, value)
+            | Self::Div(register, value)
+            | Self::Mod(register, value)
+            | Self::Eql(register, value) => match value {
+                Value::Register(other) => [Some(*register), Some(*other)],
+                Value::Constant(_) => [Some(*register), None],
+            },
+        }
+    }
 }
 
 impl TryFrom<&str> for Instruction {
@@ -106,7 +153,7 @@ impl TryFrom<&str> for Instruction {
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         let (ins, args) = value
             .split_once(' ')
-            .ok_or_else(|| anyhow::anyhow!("lol"))?;
+            .ok_or_else(|| anyhow::anyhow!("missing arguments for instruction '{}'", value))?;
 
         let res = match ins {
             "inp" => Self::Inp(args.try_into()?),
@@ -171,17 +218,13 @@ struct Alu<T> {
 }
 
 impl<T> Alu<T> {
-    fn new<I>(input: I) -> Self
+    fn new<I>(registers: Registers<T>, input: I) -> Self
     where
-        T: Default,
         I: IntoIterator<Item = T>,
         <I as IntoIterator>::IntoIter: 'static,
     {
         let input = Box::new(input.into_iter());
-        Self {
-            registers: Registers::default(),
-            input,
-        }
+        Self { registers, input }
     }
 
     fn map<F>(&mut self, register: Register, f: F)
@@ -197,16 +240,6 @@ impl<T> Alu<T> {
         &self.registers[register as usize]
     }
 
-    #[allow(unused)]
-    fn read_mut(&mut self, register: Register) -> &mut T {
-        &mut self.registers[register as usize]
-    }
-
-    #[allow(unused)]
-    fn write(&mut self, register: Register, value: T) {
-        self.registers[register as usize] = value;
-    }
-
     fn write_input_to(&mut self, register: Register) {
         self.registers[register as usize] = self.input.next().expect("need more input");
     }
@@ -218,192 +251,344 @@ impl<T: std::fmt::Debug> std::fmt::Debug for Alu<T> {
     }
 }
 
-#[derive(Debug, Clone)]
-enum Symbolic {
-    #[allow(unused)]
-    Input(u8),
-    Value(i32),
-    Term(Box<(Operation, Symbolic, Symbolic)>),
+/// All values a register can have, for any input.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+struct Interval {
+    min: i64,
+    max: i64,
 }
 
-impl std::fmt::Display for Symbolic {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Input(i) => write!(f, "i{}", i),
-            Self::Value(value) => write!(f, "{}", value),
-            Self::Term(term) => write!(f, "({}{}{})", term.1, term.0, term.2),
-        }
+impl Interval {
+    fn new(min: i64, max: i64) -> Self {
+        Self { min, max }
+    }
+
+    fn contains(&self, value: i64) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+
+    /// The smallest interval containing all values.
+    fn hull(values: impl IntoIterator<Item = i64>) -> Self {
+        values.into_iter().fold(
+            Self::new(i64::MAX, i64::MIN),
+            |Self { min, max }, value| Self::new(min.min(value), max.max(value)),
+        )
     }
-}
 
-impl Default for Symbolic {
-    fn default() -> Self {
-        Self::Value(0)
+    fn magnitude(&self) -> i64 {
+        self.min.saturating_abs().max(self.max.saturating_abs())
     }
 }
 
-impl From<i32> for Symbolic {
+impl From<i32> for Interval {
     fn from(value: i32) -> Self {
-        Symbolic::Value(value)
+        Self::new(value.into(), value.into())
     }
 }
 
-impl std::ops::Add for Symbolic {
+impl std::ops::Add for Interval {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Self::Value(0), x) => x,
-            (x, Self::Value(0)) => x,
-            (Self::Value(a), Self::Value(b)) => Self::Value(a + b),
-            (a, b) => Self::Term(Box::new((Operation::Add, a, b))),
-        }
+        Self::new(
+            self.min.saturating_add(rhs.min),
+            self.max.saturating_add(rhs.max),
+        )
     }
 }
 
-impl std::ops::Mul for Symbolic {
+impl std::ops::Mul for Interval {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Self::Value(0), _) => Self::Value(0),
-            (_, Self::Value(0)) => Self::Value(0),
-            (Self::Value(a), Self::Value(b)) => Self::Value(a * b),
-            (a, b) => Self::Term(Box::new((Operation::Mul, a, b))),
-        }
+        Self::hull([
+            self.min.saturating_mul(rhs.min),
+            self.min.saturating_mul(rhs.max),
+            self.max.saturating_mul(rhs.min),
+            self.max.saturating_mul(rhs.max),
+        ])
     }
 }
 
-impl std::ops::Div for Symbolic {
+impl std::ops::Div for Interval {
     type Output = Self;
 
     fn div(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Self::Value(1), x) => x,
-            (x, Self::Value(1)) => x,
-            (Self::Value(a), Self::Value(b)) => Self::Value(a / b),
-            (a, b) => Self::Term(Box::new((Operation::Div, a, b))),
+        if rhs.contains(0) {
+            // Dividing by anything but zero never increases the magnitude.
+            let magnitude = self.magnitude();
+            return Self::new(-magnitude, magnitude);
         }
+        Self::hull([
+            self.min.saturating_div(rhs.min),
+            self.min.saturating_div(rhs.max),
+            self.max.saturating_div(rhs.min),
+            self.max.saturating_div(rhs.max),
+        ])
     }
 }
 
-impl std::ops::Rem for Symbolic {
+impl std::ops::Rem for Interval {
     type Output = Self;
 
     fn rem(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Self::Value(a), Self::Value(b)) => Self::Value(a % b),
-            (a, b) => Self::Term(Box::new((Operation::Mod, a, b))),
+        let limit = rhs.magnitude().saturating_sub(1);
+        if self.min >= 0 && self.max < rhs.min {
+            self
+        } else if self.min >= 0 {
+            Self::new(0, self.max.min(limit))
+        } else {
+            Self::new(-limit, limit)
         }
     }
 }
 
-impl EqualOp for Symbolic {
+impl EqualOp for Interval {
     type Output = Self;
 
     fn equal(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Self::Value(a), Self::Value(b)) => Self::Value((a == b) as i32),
-            (a, b) => Self::Term(Box::new((Operation::Eql, a, b))),
+        if self.min == self.max && self == rhs {
+            Self::new(1, 1)
+        } else if self.max < rhs.min || rhs.max < self.min {
+            Self::new(0, 0)
+        } else {
+            Self::new(0, 1)
         }
     }
 }
 
-fn eval2(instructions: Vec<Instruction>) -> anyhow::Result<()> {
-    // let inputs = (0..14).map(|_| (1..=9)).multi_cartesian_product();
+/// Searches the model numbers accepted by MONAD, the program has to end with
+/// `z` being zero, digit by digit.
+///
+/// The program is split into one part per input, the state between parts are
+/// the registers. States are pruned when the interval of `z` at the end of the
+/// program over all remaining inputs does not contain zero, and states which
+/// were already found to fail are remembered, ignoring registers which are
+/// overwritten before they are read again.
+struct Monad {
+    instructions: Vec<Instruction>,
+    /// Index of the instruction each part starts at, the first part also
+    /// contains the instructions before the first input.
+    parts: Vec<usize>,
+    /// Registers whose value at the start of each part is still read later.
+    live: Vec<[bool; 4]>,
+}
 
-    // let input = vec![1, 1, 7, 8, 1, 2, 8, 7, 2, 3, 2, 3, 3, 9];
-    // let input = vec![9; 14];
-    //              [9, 1, 9, 6, 1, 1, 7, 6, 1, 2, 1, 3, 1, 9]
-    //                  X     X  X
-    let input = vec![5, 1, 6, 7, 1, 2, 8, 7, 2, 3, 2, 3, 9, 1];
-    // let mut input = vec![1, 1, 1, 1, 1, 3, 9];
+impl Monad {
+    fn new(instructions: Vec<Instruction>) -> Self {
+        let inputs = instructions
+            .iter()
+            .enumerate()
+            .filter(|(_, instruction)| matches!(instruction, Instruction::Inp(_)))
+            .map(|(i, _)| i);
+        let parts: Vec<_> = std::iter::once(0).chain(inputs.skip(1)).collect();
+
+        // Only `z` is checked at the end, anything else is dead unless it is
+        // read for a live result or to check whether the ALU crashes.
+        let mut live = [false, false, false, true];
+        let mut live_at = vec![live; instructions.len()];
+        for (i, instruction) in instructions.iter().enumerate().rev() {
+            let target = instruction.target() as usize;
+            if live[target] {
+                live[target] = false;
+                for register in instruction.reads().into_iter().flatten() {
+                    live[register as usize] = true;
+                }
+            }
+            for register in instruction.checks().into_iter().flatten() {
+                live[register as usize] = true;
+            }
+            live_at[i] = live;
+        }
+        let live = parts.iter().map(|&start| live_at[start]).collect();
 
-    let mut alu = Alu::new(input);
-    for (i, ins) in instructions.iter().enumerate() {
-        ins.evaluate(&mut alu);
+        Self {
+            instructions,
+            parts,
+            live,
+        }
+    }
+
+    fn inputs(&self) -> usize {
+        self.instructions
+            .iter()
+            .filter(|instruction| matches!(instruction, Instruction::Inp(_)))
+            .count()
+    }
+
+    /// The instructions of `part`, they read at most one input.
+    fn part(&self, part: usize) -> &[Instruction] {
+        let end = self
+            .parts
+            .get(part + 1)
+            .copied()
+            .unwrap_or(self.instructions.len());
+        &self.instructions[self.parts[part]..end]
+    }
 
-        if i == 59 || i == 113 || i == 149 || i == 185 || i == 203 || i == 221 || i == 239 {
-            println!("{:>3} {:?} \t{:?}", i + 1, ins, alu.registers);
+    /// The registers after running `part` with `input`, `None` if the ALU crashes.
+    fn run(&self, part: usize, registers: Registers<i64>, input: i64) -> Option<Registers<i64>> {
+        let mut alu = Alu::new(registers, std::iter::once(input));
+        for instruction in self.part(part) {
+            if instruction.crashes(&alu) {
+                return None;
+            }
+            instruction.evaluate(&mut alu);
         }
-        if i == 60 || i == 114 || i == 150 || i == 186 || i == 204 || i == 222 || i == 240 {
-            println!("{:>3} {:?} \t{:?}", i + 1, ins, alu.registers);
+        Some(alu.registers)
+    }
+
+    /// Whether `z` can still be zero at the end when `part` starts with `registers`.
+    fn feasible(&self, part: usize, registers: Registers<i64>) -> bool {
+        let registers = registers.map(|value| Interval::new(value, value));
+        let mut alu = Alu::new(registers, std::iter::repeat(Interval::new(1, 9)));
+        for instruction in &self.instructions[self.parts[part]..] {
+            instruction.evaluate(&mut alu);
         }
+        alu.read(Register::Z).contains(0)
     }
-    println!("{:?}", alu.registers);
-    Ok(())
-}
 
-fn eval(instructions: Vec<Instruction>) -> anyhow::Result<()> {
-    // [9, 1, 8, 9, 7, 3, 9, 9, 4, 9, 8, 9, 9, 5]
-    // [5, 1, 1, 2, 1, 1, 7, 6, 1, 2, 1, 3, 9, 1]
-
-    //  5 <----------------------------------> 1
-    //     1 <----------------------------> 9
-    //              1 <----------------> 3
-    //        1<>2     1<>7  6<>1  2<>1
-    //                       ?  ?        ?
-    //  0  1  2  3  4  5  6  7  8  9 10 11 12 13
-    // [1, 1, 1, 1, 1, 3, 9, 9, 4, 9, 8, 3, 2, 9]
-    // [X, X, X, ?, ?, 1, 7, 8, 3, 9, 8, ?, 2, 9]
-    //                 2, 8  7, 2  2, 1     1, 8
-    //                 9, 9  6, 1  3, 2
-    //                             4, 3
-    //                             5, 4
-    //                             6, 5
-    //                             7, 6
-    //                             8, 7
-
-    // let inputs = vec![vec![9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9]].into_iter();
-    // [9, 9, 9, 9, 9, 3, 9]
-    // PART 1
-    let inputs = (0..7).map(|_| (1..=9).rev()).multi_cartesian_product(); // input
-    let mut result = Vec::<i32>::new();
-    'part1: for input in inputs {
-        let r = input.clone();
-        let mut alu = Alu::new(r);
-        for (i, ins) in instructions.iter().enumerate() {
-            ins.evaluate(&mut alu);
-
-            if i == 114 {
-                // println!("{:?}", input);
-                // println!("{:?} {:?} {}", ins, alu.registers, alu.registers[3] % 26);
-                if *alu.read(Register::X) == 1 && *alu.read(Register::Z) % 26 > 20 {
-                    result.extend(&input[0..7]);
-                    break 'part1;
-                }
-                break;
+    /// The first accepted model number with the digits tried in the order of `digits`.
+    fn search(&self, digits: [i64; 9]) -> Option<u64> {
+        let mut failed = HashSet::new();
+        let mut number = Vec::with_capacity(self.parts.len());
+        self.search_part(0, [0; 4], &digits, &mut failed, &mut number)
+            .then(|| number.iter().fold(0, |number, &digit| number * 10 + digit as u64))
+    }
+
+    fn search_part(
+        &self,
+        part: usize,
+        mut registers: Registers<i64>,
+        digits: &[i64; 9],
+        failed: &mut HashSet<(usize, Registers<i64>)>,
+        number: &mut Vec<i64>,
+    ) -> bool {
+        if part == self.parts.len() {
+            return registers[Register::Z as usize] == 0;
+        }
+
+        for (value, live) in registers.iter_mut().zip(self.live[part]) {
+            if !live {
+                *value = 0;
             }
         }
-    }
+        if failed.contains(&(part, registers)) || !self.feasible(part, registers) {
+            return false;
+        }
 
-    println!("P1: {:?}", result);
+        for &digit in digits {
+            let Some(next) = self.run(part, registers, digit) else {
+                continue;
+            };
+            number.push(digit);
+            if self.search_part(part + 1, next, digits, failed, number) {
+                return true;
+            }
+            number.pop();
+        }
 
-    Ok(())
+        failed.insert((part, registers));
+        false
+    }
 }
 
-pub fn part1(reader: Input) -> anyhow::Result<i32> {
+fn read(reader: Input) -> anyhow::Result<Monad> {
     let instructions = reader
         .lines()
         .map(|line| line?.as_str().try_into())
         .collect::<Result<Vec<Instruction>, _>>()?;
 
-    eval(instructions).unwrap();
-
-    Ok(0)
+    let monad = Monad::new(instructions);
+    anyhow::ensure!(monad.inputs() > 0, "The program reads no input");
+    Ok(monad)
 }
 
-pub fn part2(reader: Input) -> anyhow::Result<i32> {
-    let instructions = reader
-        .lines()
-        .map(|line| line?.as_str().try_into())
-        .collect::<Result<Vec<Instruction>, _>>()?;
+pub fn part1(reader: Input) -> anyhow::Result<u64> {
+    let monad = read(reader)?;
+    monad
+        .search([9, 8, 7, 6, 5, 4, 3, 2, 1])
+        .context("No valid model number")
+}
 
-    eval2(instructions).unwrap();
+pub fn part2(reader: Input) -> anyhow::Result<u64> {
+    let monad = read(reader)?;
+    monad
+        .search([1, 2, 3, 4, 5, 6, 7, 8, 9])
+        .context("No valid model number")
+}
 
-    Ok(0)
+aoc_common::examples! {
+    // The second digit has to be the first one plus three.
+    offset: "
+inp w
+add z w
+add z 3
+inp w
+mul z -1
+add z w
+" => { part1: 69, part2: 14 }
+    // The result of the division is never used, but it would crash on a zero
+    // divisor, so the first digit has to be kept for the check.
+    unused_division: "
+inp x
+add x 1
+inp w
+div y x
+mul y 0
+add z w
+add z -5
+" => { part1: 95, part2: 15 }
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A MONAD program, every digit is checked by the same instructions with
+    /// the parameters `(div z, add x, add y)`.
+    fn monad(parameters: &[(i32, i32, i32)]) -> Input {
+        let program: String = parameters
+            .iter()
+            .map(|(div, x, y)| {
+                format!(
+                    "inp w\nmul x 0\nadd x z\nmod x 26\ndiv z {div}\nadd x {x}\neql x w\n\
+                     eql x 0\nmul y 0\nadd y 25\nmul y x\nadd y 1\nmul z y\nmul y 0\n\
+                     add y w\nadd y {y}\nmul y x\nadd z y\n"
+                )
+            })
+            .collect();
+        Input::new(std::io::Cursor::new(program))
+    }
+
+    #[test]
+    fn test_monad() {
+        let parameters = [
+            (1, 10, 15),
+            (1, 13, 7),
+            (26, -15, 12),
+            (26, -9, 5),
+            (1, 15, 13),
+            (1, 11, 10),
+            (1, 10, 15),
+            (1, 12, 1),
+            (26, -9, 1),
+            (26, -23, 15),
+            (1, 13, 11),
+            (26, -13, 7),
+            (26, -18, 9),
+            (26, -14, 13),
+        ];
+        assert_eq!(part1(monad(&parameters)).unwrap(), 39199999119718);
+        assert_eq!(part2(monad(&parameters)).unwrap(), 19172999113111);
+    }
+
+    #[test]
+    fn test_overflow() {
+        // Any digit but 5 overflows, which crashes the ALU.
+        let program = "inp w\nadd z w\nadd z -5\n\
+                       mul z 2147483647\nmul z 2147483647\nmul z 2147483647\n";
+        assert_eq!(part1(Input::from(program)).unwrap(), 5);
+        assert_eq!(part2(Input::from(program)).unwrap(), 5);
+    }
 }
@@ -1,7 +1,8 @@
-use aoc2021::grid::Grid;
-use aoc2021::Input;
+use crate::automaton::{Automaton, Neighborhood, Rule};
+use crate::grid::Border;
+use crate::{cli, pnm, Input};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 enum Seafloor {
     Empty,
     CucumberEast,
@@ -13,12 +14,12 @@ impl Seafloor {
         matches!(self, Self::Empty)
     }
 
-    fn is_east(&self) -> bool {
-        matches!(self, Self::CucumberEast)
-    }
-
-    fn is_south(&self) -> bool {
-        matches!(self, Self::CucumberSouth)
+    fn color(&self) -> [u8; 3] {
+        match self {
+            Self::Empty => [0, 24, 64],
+            Self::CucumberEast => [255, 160, 0],
+            Self::CucumberSouth => [0, 200, 120],
+        }
     }
 }
 
@@ -32,91 +33,75 @@ impl std::fmt::Display for Seafloor {
     }
 }
 
-impl From<u8> for Seafloor {
-    fn from(val: u8) -> Self {
+impl TryFrom<u8> for Seafloor {
+    type Error = anyhow::Error;
+
+    fn try_from(val: u8) -> Result<Self, Self::Error> {
         match val {
-            b'>' => Self::CucumberEast,
-            b'v' => Self::CucumberSouth,
-            b'.' => Self::Empty,
-            _ => panic!("invalid input"),
+            b'>' => Ok(Self::CucumberEast),
+            b'v' => Ok(Self::CucumberSouth),
+            b'.' => Ok(Self::Empty),
+            _ => anyhow::bail!("Invalid sea floor"),
         }
     }
 }
 
-fn step(seafloor: &mut Grid<Seafloor>) -> bool {
-    let mut moved = false;
-
-    for y in 0..seafloor.height() {
-        let first = (0, y);
-        let last = (seafloor.width() - 1, y);
-        let first_is_empty = seafloor[first].is_empty();
-        let last_is_east = seafloor[last].is_east();
-
-        let mut just_moved = false;
-        for x in 0..seafloor.width() - 1 {
-            if just_moved {
-                just_moved = false;
-                continue;
-            }
-
-            let next = (x + 1, y);
-            if seafloor[(x, y)].is_east() && seafloor[next].is_empty() {
-                seafloor.swap((x, y), next);
-                just_moved = true;
-                moved = true;
-            }
-        }
+/// The east facing herd moves first, then the south facing one, a sea
+/// cucumber moves if the cell in front of it was empty before its herd moved.
+struct Herds;
 
-        if first_is_empty && last_is_east {
-            seafloor.swap(first, last);
-            moved = true;
-        }
+impl Rule<Seafloor> for Herds {
+    fn phases(&self) -> usize {
+        2
     }
 
-    for x in 0..seafloor.width() {
-        let first = (x, 0);
-        let last = (x, seafloor.height() - 1);
-        let first_is_empty = seafloor[first].is_empty();
-        let last_is_south = seafloor[last].is_south();
-
-        let mut just_moved = false;
-        for y in 0..seafloor.height() - 1 {
-            if just_moved {
-                just_moved = false;
-                continue;
-            }
-
-            let next = (x, y + 1);
-            if seafloor[(x, y)].is_south() && seafloor[next].is_empty() {
-                seafloor.swap((x, y), next);
-                just_moved = true;
-                moved = true;
-            }
-        }
-
-        if first_is_empty && last_is_south {
-            seafloor.swap(first, last);
-            moved = true;
+    fn update(&mut self, phase: usize, cells: &Neighborhood<'_, Seafloor>) -> Seafloor {
+        let (herd, dx, dy) = match phase {
+            0 => (Seafloor::CucumberEast, 1, 0),
+            _ => (Seafloor::CucumberSouth, 0, 1),
+        };
+
+        let cell = cells.center();
+        if cell.is_empty() && cells.get(-dx, -dy) == herd {
+            herd
+        } else if cell == herd && cells.get(dx, dy).is_empty() {
+            Seafloor::Empty
+        } else {
+            cell
         }
     }
-
-    moved
 }
 
 pub fn part1(mut reader: Input) -> anyhow::Result<usize> {
-    let mut seafloor = Grid::<Seafloor>::read(&mut reader);
-    for i in 1.. {
-        if !step(&mut seafloor) {
-            return Ok(i);
+    let mut seafloor = Automaton::new(reader.grid::<Seafloor>()?, 1, Border::Wrap);
+    if !cli::visualize() {
+        return Ok(seafloor.run_until_stable(&mut Herds));
+    }
+
+    let mut frames = pnm::Frames::new("frames/2021/day25")?;
+    loop {
+        frames.write(seafloor.grid(), Seafloor::color)?;
+        if !seafloor.step(&mut Herds) {
+            eprintln!("Wrote {} frames to {:?}", frames.len(), frames.dir());
+            return Ok(seafloor.steps());
         }
     }
-    unreachable!()
 }
 
 pub fn part2(_reader: Input) -> anyhow::Result<usize> {
     Ok(0)
 }
 
-pub fn main() {
-    aoc2021::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+v...>>.vv>
+.vv>>.vv..
+>>.>v>...v
+>>v>>.>.v.
+v>v.vv.v..
+>.>>..v...
+.vv..>.>v.
+v.v..>>v.v
+....v..v.>
+" => { part1: 58 }
 }
@@ -1,4 +1,7 @@
-pub mod cli;
-pub mod grid;
+pub use aoc_common::{automaton, cli, grid, pnm, search, sparse_grid, Input};
 
-pub type Input = Box<dyn std::io::BufRead>;
+aoc_common::solutions!(
+    day01, day02, day03, day04, day04_2, day05, day06, day07, day08, day09, day10, day11, day12,
+    day13, day14, day15, day16, day17, day18, day19 [stub part2], day20, day21, day22, day23,
+    day24, day25 [stub part2],
+);
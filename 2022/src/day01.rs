@@ -1,5 +1,4 @@
-use aoc2022::Input;
-use std::io::BufRead;
+use crate::Input;
 
 struct Max<const N: usize>([u32; N]);
 
@@ -9,11 +8,8 @@ impl<const N: usize> Max<N> {
     }
 
     pub fn push(&mut self, new_val: u32) {
-        for v in self.0.iter_mut().rev() {
-            if *v < new_val {
-                *v = new_val;
-                break;
-            }
+        if let Some(min) = self.0.iter_mut().min() {
+            *min = (*min).max(new_val);
         }
     }
 
@@ -28,42 +24,51 @@ impl<const N: usize> Default for Max<N> {
     }
 }
 
-pub fn part1(reader: Input) -> anyhow::Result<u32> {
-    let mut current = 0;
-    let mut max = 0;
+fn calories(reader: &mut Input) -> impl Iterator<Item = anyhow::Result<u32>> + '_ {
+    reader.blocks().map(|block| {
+        let sum = block?
+            .iter()
+            .map(|line| line.parse::<u32>())
+            .sum::<Result<_, _>>()?;
+        Ok(sum)
+    })
+}
 
-    for line in reader.lines() {
-        let line = line.unwrap();
+pub fn part1(mut reader: Input) -> anyhow::Result<u32> {
+    let mut max = 0;
 
-        if line.is_empty() {
-            max = max.max(current);
-            current = 0;
-        } else {
-            current += line.parse::<u32>().unwrap();
-        }
+    for calories in calories(&mut reader) {
+        max = max.max(calories?);
     }
 
     Ok(max)
 }
 
-pub fn part2(reader: Input) -> anyhow::Result<u32> {
-    let mut current = 0;
+pub fn part2(mut reader: Input) -> anyhow::Result<u32> {
     let mut max = Max::<3>::new();
 
-    for line in reader.lines() {
-        let line = line.unwrap();
-
-        if line.is_empty() {
-            max.push(current);
-            current = 0;
-        } else {
-            current += line.parse::<u32>().unwrap();
-        }
+    for calories in calories(&mut reader) {
+        max.push(calories?);
     }
 
     Ok(max.sum())
 }
 
-pub fn main() {
-    aoc2022::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+1000
+2000
+3000
+
+4000
+
+5000
+6000
+
+7000
+8000
+9000
+
+10000
+" => { part1: 24000, part2: 45000 }
 }
@@ -1,4 +1,4 @@
-use aoc2022::Input;
+use crate::Input;
 use std::{io::BufRead, str::FromStr};
 
 #[derive(Copy, Clone)]
@@ -121,6 +121,10 @@ pub fn part2(reader: Input) -> anyhow::Result<u32> {
     Ok(score)
 }
 
-pub fn main() {
-    aoc2022::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+A Y
+B X
+C Z
+" => { part1: 15, part2: 12 }
 }
@@ -1,4 +1,4 @@
-use aoc2022::Input;
+use crate::Input;
 use itertools::Itertools;
 use std::io::BufRead;
 
@@ -22,7 +22,7 @@ impl State {
 }
 
 fn find_duplicates(inp: &[u8]) -> impl Iterator<Item = u8> + '_ {
-    debug_assert!(inp.len() % 2 == 0);
+    debug_assert!(inp.len().is_multiple_of(2));
 
     let half = inp.len() / 2;
 
@@ -93,6 +93,13 @@ pub fn part2(reader: Input) -> anyhow::Result<usize> {
     Ok(r)
 }
 
-pub fn main() {
-    aoc2022::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw
+" => { part1: 157, part2: 70 }
 }
@@ -1,4 +1,4 @@
-use aoc2022::Input;
+use crate::Input;
 use std::{io::BufRead, ops::RangeInclusive};
 
 type Sections = RangeInclusive<u32>;
@@ -39,6 +39,13 @@ pub fn part2(reader: Input) -> anyhow::Result<u32> {
     Ok(r as u32)
 }
 
-pub fn main() {
-    aoc2022::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+2-4,6-8
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8
+" => { part1: 2, part2: 4 }
 }
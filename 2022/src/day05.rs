@@ -1,4 +1,4 @@
-use aoc2022::Input;
+use crate::Input;
 use std::{collections::VecDeque, io::BufRead};
 
 #[derive(Default, Debug)]
@@ -89,6 +89,16 @@ pub fn part2(reader: Input) -> anyhow::Result<String> {
     Ok(board.tops())
 }
 
-pub fn main() {
-    aoc2022::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2
+" => { part1: "CMZ", part2: "MCD" }
 }
@@ -1,4 +1,4 @@
-use aoc2022::Input;
+use crate::Input;
 use itertools::Itertools;
 use std::io::Read;
 
@@ -37,6 +37,10 @@ pub fn part2(mut reader: Input) -> anyhow::Result<usize> {
     Ok(r)
 }
 
-pub fn main() {
-    aoc2022::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    first: "mjqjpqmgbljsphdztnvjfqwrcgsmlb" => { part1: 7, part2: 19 }
+    second: "bvwbjplbgvbhsrlpgdmjqwftvncz" => { part1: 5, part2: 23 }
+    third: "nppdvjthqldpwncqszvftbrmjlhg" => { part1: 6, part2: 23 }
+    fourth: "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg" => { part1: 10, part2: 29 }
+    fifth: "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw" => { part1: 11, part2: 26 }
 }
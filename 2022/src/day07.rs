@@ -1,4 +1,4 @@
-use aoc2022::Input;
+use crate::Input;
 use std::{
     cell::RefCell,
     cmp::Reverse,
@@ -241,6 +241,30 @@ pub fn part2(reader: Input) -> anyhow::Result<usize> {
     unreachable!()
 }
 
-pub fn main() {
-    aoc2022::cli::run(part1, part2).unwrap();
+aoc_common::examples! {
+    example: "
+$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k
+" => { part1: 95437, part2: 24933642 }
 }
@@ -1,4 +1,3 @@
-pub mod cli;
-pub mod grid;
+pub use aoc_common::{automaton, cli, grid, pnm, search, sparse_grid, Input};
 
-pub type Input = std::io::BufReader<Box<dyn std::io::Read>>;
+aoc_common::solutions!(day01, day02, day03, day04, day05, day06, day07, day08);
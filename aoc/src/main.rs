@@ -0,0 +1,26 @@
+use clap::Parser;
+use std::ffi::OsString;
+
+#[derive(Parser)]
+#[command(about = "Runs a solution of any year, e.g. `aoc 2021 15 -2 input.txt`")]
+struct Opts {
+    year: u16,
+
+    /// Arguments for the year's runner: the day, the part and the input.
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<OsString>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let opts = Opts::parse();
+
+    let args = std::iter::once(OsString::from(format!("aoc {}", opts.year))).chain(opts.args);
+
+    match opts.year {
+        2021 => aoc2021::cli::run(2021, aoc2021::SOLUTIONS, args)?,
+        2022 => aoc2022::cli::run(2022, aoc2022::SOLUTIONS, args)?,
+        year => anyhow::bail!("No solutions for year {year}"),
+    };
+
+    Ok(())
+}
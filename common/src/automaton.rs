@@ -0,0 +1,417 @@
+//! Cellular automata on a `Grid`, every step updates all cells at once from
+//! their neighbourhood in the previous state.
+
+use crate::grid::{Border, Grid};
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// The cells within the radius of a cell, the argument of a `Rule`.
+pub struct Neighborhood<'a, T> {
+    cells: &'a [T],
+    radius: usize,
+}
+
+impl<'a, T: Copy> Neighborhood<'a, T> {
+    /// The cell `(dx, dy)` away from the center, both have to be within the radius.
+    pub fn get(&self, dx: isize, dy: isize) -> T {
+        let size = self.radius * 2 + 1;
+        let x = self.radius.checked_add_signed(dx).filter(|&x| x < size);
+        let y = self.radius.checked_add_signed(dy).filter(|&y| y < size);
+        match (x, y) {
+            (Some(x), Some(y)) => self.cells[x + y * size],
+            _ => panic!("({dx}, {dy}) is outside of the radius {}", self.radius),
+        }
+    }
+
+    /// The cell which is updated.
+    pub fn center(&self) -> T {
+        self.cells[self.cells.len() / 2]
+    }
+
+    /// All cells in row-major order, including the center.
+    pub fn iter(&self) -> impl Iterator<Item = T> + 'a {
+        self.cells.iter().copied()
+    }
+
+    /// All cells except the center.
+    pub fn neighbors(&self) -> impl Iterator<Item = T> + 'a {
+        let center = self.cells.len() / 2;
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(move |&(i, _)| i != center)
+            .map(|(_, &cell)| cell)
+    }
+}
+
+/// The update rule of an automaton.
+///
+/// A step consists of one or more phases, each phase updates all cells at once
+/// on the result of the previous phase, e.g. first the east facing and then
+/// the south facing herd of sea cucumbers.
+///
+/// Closures taking a `&Neighborhood` are rules with a single phase.
+pub trait Rule<T> {
+    fn phases(&self) -> usize {
+        1
+    }
+
+    /// Whether `phase` is repeated until it does not change any cell anymore,
+    /// e.g. to propagate a chain reaction within a single step.
+    fn repeat(&self, _phase: usize) -> bool {
+        false
+    }
+
+    /// The next value of the center cell of `cells`.
+    fn update(&mut self, phase: usize, cells: &Neighborhood<'_, T>) -> T;
+}
+
+impl<T, F> Rule<T> for F
+where
+    F: FnMut(&Neighborhood<'_, T>) -> T,
+{
+    fn update(&mut self, _phase: usize, cells: &Neighborhood<'_, T>) -> T {
+        self(cells)
+    }
+}
+
+/// How the automaton treats cells outside of the grid.
+#[derive(Debug, Copy, Clone)]
+enum Edge<T> {
+    Border(Border<T>),
+    /// The grid is surrounded by infinitely many cells of the value, the grid
+    /// grows by the radius once before every phase and the background is
+    /// updated like any other cell.
+    ///
+    /// A repeated phase does not grow the grid again, within one step it
+    /// spreads at most the radius beyond the grid of the previous phase.
+    Background(T),
+}
+
+#[derive(Debug, Clone)]
+pub struct Automaton<T> {
+    grid: Grid<T>,
+    radius: usize,
+    edge: Edge<T>,
+    /// How far the grid grew on every side, the position of the initial grid in it.
+    padding: usize,
+    steps: usize,
+}
+
+impl<T: Copy + PartialEq> Automaton<T> {
+    /// An automaton on the finite `grid`, `border` provides the cells outside of it.
+    ///
+    /// Panics for `Border::Skip`, every cell needs a full neighbourhood.
+    pub fn new(grid: Grid<T>, radius: usize, border: Border<T>) -> Self {
+        assert!(
+            !matches!(border, Border::Skip),
+            "automata need a cell for every position outside of the grid"
+        );
+        Self {
+            grid,
+            radius,
+            edge: Edge::Border(border),
+            padding: 0,
+            steps: 0,
+        }
+    }
+
+    /// An automaton on an infinite plane with `grid` in the middle of
+    /// infinitely many `background` cells.
+    pub fn infinite(grid: Grid<T>, radius: usize, background: T) -> Self {
+        Self {
+            grid,
+            radius,
+            edge: Edge::Background(background),
+            padding: 0,
+            steps: 0,
+        }
+    }
+
+    pub fn grid(&self) -> &Grid<T> {
+        &self.grid
+    }
+
+    pub fn into_grid(self) -> Grid<T> {
+        self.grid
+    }
+
+    /// The value of all cells outside of the grid of an infinite automaton.
+    pub fn background(&self) -> Option<T> {
+        match self.edge {
+            Edge::Border(_) => None,
+            Edge::Background(background) => Some(background),
+        }
+    }
+
+    /// The number of steps done so far.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Does one step, returns whether any cell changed.
+    pub fn step(&mut self, rule: &mut impl Rule<T>) -> bool {
+        let mut changed = false;
+        for phase in 0..rule.phases() {
+            if let Edge::Background(background) = self.edge {
+                self.grid = self.padded(background);
+                self.padding += self.radius;
+            }
+            loop {
+                let phase_changed = self.update(rule, phase);
+                changed |= phase_changed;
+                if !phase_changed || !rule.repeat(phase) {
+                    break;
+                }
+            }
+        }
+        self.steps += 1;
+        changed
+    }
+
+    /// Does `steps` steps.
+    pub fn run(&mut self, rule: &mut impl Rule<T>, steps: usize) {
+        for _ in 0..steps {
+            self.step(rule);
+        }
+    }
+
+    /// Steps until `done` holds for the grid, returns the number of that step.
+    pub fn run_until(
+        &mut self,
+        rule: &mut impl Rule<T>,
+        mut done: impl FnMut(&Grid<T>) -> bool,
+    ) -> usize {
+        loop {
+            self.step(rule);
+            if done(&self.grid) {
+                return self.steps;
+            }
+        }
+    }
+
+    /// Steps until the automaton is in a state it was in before, either a steady
+    /// state or a cycle, returns the number of the step which repeated a state.
+    ///
+    /// For a steady state that is the first step which did not change any cell.
+    /// On an infinite plane a pattern which keeps growing or moving never
+    /// repeats a state, this does not return then.
+    pub fn run_until_stable(&mut self, rule: &mut impl Rule<T>) -> usize
+    where
+        T: Eq + Hash,
+    {
+        let mut seen = HashSet::new();
+        seen.insert(self.state());
+        loop {
+            // Without a change the state is the previous one, no need to look it up.
+            if !self.step(rule) || !seen.insert(self.state()) {
+                return self.steps;
+            }
+        }
+    }
+
+    /// Everything which determines the next steps, except for the rule.
+    ///
+    /// The grid of an infinite automaton grows every phase, the rows and columns
+    /// at its edges which only contain the background are not part of the state,
+    /// only the position of the remaining cells relative to the initial grid.
+    fn state(&self) -> (isize, isize, usize, Vec<T>, Option<T>) {
+        let (mut x0, mut y0) = (0, 0);
+        let (mut x1, mut y1) = (self.grid.width(), self.grid.height());
+        if let Edge::Background(background) = self.edge {
+            let grid = &self.grid;
+            let row = |y, x0, x1| (x0..x1).all(|x| grid[(x, y)] == background);
+            let column = |x, y0, y1| (y0..y1).all(|y| grid[(x, y)] == background);
+            while y0 < y1 && row(y0, x0, x1) {
+                y0 += 1;
+            }
+            while y0 < y1 && row(y1 - 1, x0, x1) {
+                y1 -= 1;
+            }
+            while x0 < x1 && column(x0, y0, y1) {
+                x0 += 1;
+            }
+            while x0 < x1 && column(x1 - 1, y0, y1) {
+                x1 -= 1;
+            }
+        }
+
+        let cells = (y0..y1)
+            .flat_map(|y| (x0..x1).map(move |x| self.grid[(x, y)]))
+            .collect();
+        let origin = |start: usize| start as isize - self.padding as isize;
+        (origin(x0), origin(y0), x1 - x0, cells, self.background())
+    }
+
+    /// Updates all cells once with `phase` of the rule.
+    fn update(&mut self, rule: &mut impl Rule<T>, phase: usize) -> bool {
+        let border = match self.edge {
+            Edge::Border(border) => border,
+            Edge::Background(background) => Border::Constant(background),
+        };
+        let grid = &self.grid;
+
+        let mut cells = Vec::new();
+        let mut changed = false;
+        let next = Grid::from_fn(grid.width(), grid.height(), |pos| {
+            cells.clear();
+            cells.extend(grid.kernel_at(pos, self.radius, border));
+            let cell = rule.update(
+                phase,
+                &Neighborhood {
+                    cells: &cells,
+                    radius: self.radius,
+                },
+            );
+            changed |= cell != grid[pos];
+            cell
+        });
+
+        if let Edge::Background(background) = self.edge {
+            let size = self.radius * 2 + 1;
+            cells.clear();
+            cells.resize(size * size, background);
+            let cell = rule.update(
+                phase,
+                &Neighborhood {
+                    cells: &cells,
+                    radius: self.radius,
+                },
+            );
+            changed |= cell != background;
+            self.edge = Edge::Background(cell);
+        }
+
+        self.grid = next;
+        changed
+    }
+
+    /// The grid surrounded by `radius` cells of the background on every side,
+    /// the cells which can be influenced by the grid in the next phase.
+    fn padded(&self, background: T) -> Grid<T> {
+        let r = self.radius;
+        let (width, height) = (self.grid.width(), self.grid.height());
+        Grid::from_fn(width + 2 * r, height + 2 * r, |(x, y)| {
+            if (r..width + r).contains(&x) && (r..height + r).contains(&y) {
+                self.grid[(x - r, y - r)]
+            } else {
+                background
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn life(cells: &Neighborhood<'_, bool>) -> bool {
+        let alive = cells.neighbors().filter(|&alive| alive).count();
+        alive == 3 || (cells.center() && alive == 2)
+    }
+
+    #[test]
+    fn test_blinker() {
+        let grid = Grid::new([false, true, false].repeat(3), 3, 3);
+        let mut automaton = Automaton::new(grid.clone(), 1, Border::Constant(false));
+
+        assert!(automaton.step(&mut life));
+        assert_eq!(automaton.grid().data(), grid.transpose().data());
+        automaton.run(&mut life, 3);
+        assert_eq!(automaton.grid().data(), grid.data());
+        assert_eq!(automaton.steps(), 4);
+    }
+
+    #[test]
+    fn test_run_until_stable() {
+        // A block and a single cell in the opposite corner which dies in the first step.
+        let mut grid = Grid::new(vec![false; 16], 4, 4);
+        for pos in [(0, 0), (1, 0), (0, 1), (1, 1), (3, 3)] {
+            grid[pos] = true;
+        }
+        let mut automaton = Automaton::new(grid, 1, Border::Constant(false));
+        assert_eq!(automaton.run_until_stable(&mut life), 2);
+    }
+
+    #[test]
+    fn test_run_until_stable_cycle() {
+        // The blinker oscillates with a period of two.
+        let grid = Grid::new([false, true, false].repeat(3), 3, 3);
+        let mut automaton = Automaton::new(grid, 1, Border::Constant(false));
+        assert_eq!(automaton.run_until_stable(&mut life), 2);
+        assert_eq!(automaton.grid().data(), [false, true, false].repeat(3));
+    }
+
+    #[test]
+    fn test_infinite_cycle() {
+        // The background and the single cell flip every step.
+        let mut flip = |cells: &Neighborhood<'_, u8>| 1 - cells.center();
+        let mut automaton = Automaton::infinite(Grid::new(vec![1], 1, 1), 1, 0);
+        assert_eq!(automaton.run_until_stable(&mut flip), 2);
+        assert_eq!(automaton.background(), Some(0));
+    }
+
+    #[test]
+    fn test_infinite_repeat() {
+        struct Spread;
+
+        // Every cell takes the maximum of its neighbourhood until nothing changes.
+        impl Rule<u8> for Spread {
+            fn repeat(&self, _phase: usize) -> bool {
+                true
+            }
+
+            fn update(&mut self, _phase: usize, cells: &Neighborhood<'_, u8>) -> u8 {
+                cells.iter().max().unwrap()
+            }
+        }
+
+        let mut automaton = Automaton::infinite(Grid::new(vec![1], 1, 1), 1, 0);
+        automaton.step(&mut Spread);
+        assert_eq!(automaton.grid().width(), 3);
+        assert!(automaton.grid().data().iter().all(|&cell| cell == 1));
+    }
+
+    #[test]
+    fn test_infinite() {
+        // Every cell becomes the inverse of the sum of its neighbourhood.
+        let mut invert = |cells: &Neighborhood<'_, u8>| (cells.iter().sum::<u8>() == 0) as u8;
+        let mut automaton = Automaton::infinite(Grid::new(vec![1], 1, 1), 1, 0);
+
+        automaton.step(&mut invert);
+        assert_eq!(automaton.background(), Some(1));
+        assert_eq!(
+            (automaton.grid().width(), automaton.grid().height()),
+            (3, 3)
+        );
+        assert!(automaton.grid().data().iter().all(|&cell| cell == 0));
+
+        automaton.step(&mut invert);
+        assert_eq!(automaton.background(), Some(0));
+        assert_eq!(automaton.grid().width(), 5);
+    }
+
+    #[test]
+    fn test_phases() {
+        struct Shift;
+
+        // Moves every `1` one to the right and then one down, wrapping around.
+        impl Rule<u8> for Shift {
+            fn phases(&self) -> usize {
+                2
+            }
+
+            fn update(&mut self, phase: usize, cells: &Neighborhood<'_, u8>) -> u8 {
+                match phase {
+                    0 => cells.get(-1, 0),
+                    _ => cells.get(0, -1),
+                }
+            }
+        }
+
+        let grid = Grid::new(vec![1, 0, 0, 0], 2, 2);
+        let mut automaton = Automaton::new(grid, 1, Border::Wrap);
+        automaton.step(&mut Shift);
+        assert_eq!(automaton.grid().data(), &[0, 0, 0, 1]);
+    }
+}
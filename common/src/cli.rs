@@ -0,0 +1,708 @@
+use crate::Input;
+use anyhow::Context;
+use clap::Parser;
+use itertools::{EitherOrBoth, Itertools};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fmt::{self, Write};
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    fn number(&self) -> u8 {
+        match self {
+            Self::One => 1,
+            Self::Two => 2,
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::One => write!(f, "Part 1"),
+            Self::Two => write!(f, "Part 2"),
+        }
+    }
+}
+
+/// A registered solution for one day of the year.
+///
+/// Answers are converted to strings, so days returning different types
+/// can live in the same registry.
+pub struct Solution {
+    pub name: &'static str,
+    pub part1: fn(Input) -> anyhow::Result<String>,
+    pub part2: fn(Input) -> anyhow::Result<String>,
+    /// A part which is not solved (yet) and only returns a placeholder.
+    pub stub: Option<Part>,
+}
+
+impl Solution {
+    /// The day of the month, e.g. `4` for `day04` and `day04_2`.
+    pub fn day(&self) -> u8 {
+        self.name
+            .trim_start_matches("day")
+            .split('_')
+            .next()
+            .and_then(|day| day.parse().ok())
+            .unwrap_or_default()
+    }
+
+    pub fn part(&self, part: Part) -> fn(Input) -> anyhow::Result<String> {
+        match part {
+            Part::One => self.part1,
+            Part::Two => self.part2,
+        }
+    }
+
+    /// Whether `day` selects this solution, e.g. `4`, `04`, `day04` or `04_2`.
+    pub fn matches(&self, day: &str) -> bool {
+        let day = day.trim_start_matches("day");
+        let (number, variant) = match day.split_once('_') {
+            Some((number, variant)) => (number, Some(variant)),
+            None => (day, None),
+        };
+
+        let name = match (number.parse::<u8>(), variant) {
+            (Ok(number), Some(variant)) => format!("day{number:02}_{variant}"),
+            (Ok(number), None) => format!("day{number:02}"),
+            (Err(_), _) => return false,
+        };
+
+        self.name == name
+    }
+}
+
+/// Declares the day modules of a year and builds the `SOLUTIONS` registry from them.
+///
+/// A part which is not solved is marked as a stub, e.g. `day25 [stub part2]`.
+#[macro_export]
+macro_rules! solutions {
+    ($($day:ident $([stub $stub:ident])?),* $(,)?) => {
+        $(pub mod $day;)*
+
+        pub const SOLUTIONS: &[$crate::cli::Solution] = &[
+            $($crate::cli::Solution {
+                name: stringify!($day),
+                part1: |input| $day::part1(input).map(|r| r.to_string()),
+                part2: |input| $day::part2(input).map(|r| r.to_string()),
+                stub: $crate::solutions!(@stub $($stub)?),
+            },)*
+        ];
+    };
+    (@stub) => { None };
+    (@stub part1) => { Some($crate::cli::Part::One) };
+    (@stub part2) => { Some($crate::cli::Part::Two) };
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            format => Err(format!("unknown format {format:?}")),
+        }
+    }
+}
+
+/// One line of `--format json` output, describing a single part.
+#[derive(Serialize)]
+struct Record<'a> {
+    year: u16,
+    day: u8,
+    /// The registered solution, tells apart solutions of the same day, e.g. `day04_2`.
+    solution: &'a str,
+    part: u8,
+    answer: Option<&'a str>,
+    duration_ns: u128,
+    error: Option<String>,
+}
+
+#[derive(Parser)]
+struct Opts {
+    #[arg(required_unless_present = "all")]
+    day: Option<String>,
+
+    /// Run both parts of every day on its default input and print a summary table
+    #[arg(long, conflicts_with_all = ["day", "input", "part1", "part2", "bench", "format", "visualize"])]
+    all: bool,
+
+    #[arg(short, long)]
+    time: bool,
+
+    /// Run part 1, combine with `-2` to run both parts on the same input
+    #[arg(short = '1', long)]
+    part1: bool,
+    /// Run part 2, combine with `-1` to run both parts on the same input
+    #[arg(short = '2', long)]
+    part2: bool,
+
+    /// Run each part N times and report statistics of the execution times
+    #[arg(long, value_name = "N")]
+    bench: Option<usize>,
+
+    /// Output format of the answers, `text` or `json`
+    #[arg(long, default_value = "text")]
+    format: Format,
+
+    /// Let parts which support it render a visualisation to stderr
+    #[arg(long, conflicts_with = "bench")]
+    visualize: bool,
+
+    /// TOML file with the expected answers, e.g. `[day01]` with `part1 = 7`
+    #[arg(long, value_name = "FILE")]
+    expect: Option<PathBuf>,
+
+    /// Input file, `-` reads stdin [default: `inputs/<year>/dayNN.txt`, the
+    /// directory can be changed with `AOC_INPUTS`]
+    input: Option<PathBuf>,
+}
+
+impl Opts {
+    fn parts(&self) -> Vec<Part> {
+        match (self.part1, self.part2) {
+            (true, true) => vec![Part::One, Part::Two],
+            (false, true) => vec![Part::Two],
+            _ => vec![Part::One],
+        }
+    }
+}
+
+/// Environment variable with the directory of the default inputs.
+const INPUTS_VAR: &str = "AOC_INPUTS";
+
+/// The default input of a day in the inputs directory, e.g. `inputs/2021/day04.txt`.
+fn default_input(inputs: &Path, year: u16, day: u8) -> PathBuf {
+    inputs
+        .join(year.to_string())
+        .join(format!("day{day:02}.txt"))
+}
+
+/// Resolves the input to read, falls back to the default input of the day.
+fn resolve_input(input: Option<PathBuf>, year: u16, day: u8) -> anyhow::Result<PathBuf> {
+    if let Some(input) = input {
+        return Ok(input);
+    }
+
+    let inputs =
+        std::env::var_os(INPUTS_VAR).map_or_else(|| PathBuf::from("inputs"), PathBuf::from);
+    let path = default_input(&inputs, year, day);
+    anyhow::ensure!(
+        path.is_file(),
+        "No input given and the default input {path:?} does not exist, \
+         pass an input file, `-` to read stdin or set {INPUTS_VAR} to the inputs directory"
+    );
+    Ok(path)
+}
+
+/// Reads the whole input into memory, so it can be fed to more than one part.
+fn read_input(path: &Path) -> anyhow::Result<Rc<[u8]>> {
+    let mut data = Vec::new();
+    if path == Path::new("-") {
+        std::io::stdin()
+            .read_to_end(&mut data)
+            .context("Failed to read stdin")?;
+    } else {
+        File::open(path)
+            .and_then(|mut file| file.read_to_end(&mut data))
+            .with_context(|| format!("Failed to read file {path:?}"))?;
+    }
+    Ok(data.into())
+}
+
+/// An expected answer, numbers can be written without quotes.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Answer {
+    Number(i64),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "{number}"),
+            Self::Text(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+struct Expected {
+    part1: Option<Answer>,
+    part2: Option<Answer>,
+}
+
+impl Expected {
+    fn load(path: &Path, solution: &Solution) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read file {path:?}"))?;
+        let mut answers: HashMap<String, Expected> = toml::from_str(&content)
+            .with_context(|| format!("Invalid expected answers in {path:?}"))?;
+        Ok(answers.remove(solution.name).unwrap_or_default())
+    }
+
+    fn get(&self, part: Part) -> Option<&Answer> {
+        match part {
+            Part::One => self.part1.as_ref(),
+            Part::Two => self.part2.as_ref(),
+        }
+    }
+}
+
+/// Compares an answer with the expected one, ignoring trailing whitespace,
+/// and renders a line by line diff if they do not match.
+fn diff(expected: &str, actual: &str) -> Option<String> {
+    let (expected, actual) = (expected.trim_end(), actual.trim_end());
+    if expected == actual {
+        return None;
+    }
+
+    let mut result = String::new();
+    for lines in expected.lines().zip_longest(actual.lines()) {
+        let _ = match lines {
+            EitherOrBoth::Both(e, a) if e == a => writeln!(result, "  {e}"),
+            EitherOrBoth::Both(e, a) => writeln!(result, "- {e}\n+ {a}"),
+            EitherOrBoth::Left(e) => writeln!(result, "- {e}"),
+            EitherOrBoth::Right(a) => writeln!(result, "+ {a}"),
+        };
+    }
+    Some(result)
+}
+
+thread_local! {
+    static PARSED: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Marks the end of input parsing in a part.
+///
+/// Parsing ends when a part reads the end of its `Input`, parts which read
+/// their input lazily while solving call this to report the parse time
+/// separately from the solve time in `--bench`.
+pub fn parsed() {
+    PARSED.with(|parsed| parsed.set(Some(Instant::now())));
+}
+
+/// Marks the end of parsing when the input is read to its end, unless the part
+/// already marked it.
+pub(crate) fn reached_end() {
+    PARSED.with(|parsed| {
+        if parsed.get().is_none() {
+            parsed.set(Some(Instant::now()));
+        }
+    });
+}
+
+thread_local! {
+    static VISUALIZE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether `--visualize` was given, parts can then render their state to stderr.
+pub fn visualize() -> bool {
+    VISUALIZE.with(Cell::get)
+}
+
+struct Sample {
+    total: Duration,
+    parse: Option<Duration>,
+}
+
+impl Sample {
+    fn solve(&self) -> Option<Duration> {
+        self.parse.map(|parse| self.total - parse)
+    }
+}
+
+struct Stats {
+    min: Duration,
+    median: Duration,
+    mean: Duration,
+    stddev: Duration,
+}
+
+impl Stats {
+    fn new(samples: impl Iterator<Item = Duration>) -> Self {
+        let mut samples: Vec<_> = samples.collect();
+        samples.sort_unstable();
+
+        let n = samples.len();
+        let median = if n.is_multiple_of(2) {
+            (samples[n / 2 - 1] + samples[n / 2]) / 2
+        } else {
+            samples[n / 2]
+        };
+        let mean = samples.iter().sum::<Duration>() / n as u32;
+        let variance = samples
+            .iter()
+            .map(|sample| (sample.as_secs_f64() - mean.as_secs_f64()).powi(2))
+            .sum::<f64>()
+            / n as f64;
+
+        Self {
+            min: samples[0],
+            median,
+            mean,
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "min {:?}, median {:?}, mean {:?}, stddev {:?}",
+            self.min, self.median, self.mean, self.stddev
+        )
+    }
+}
+
+fn measure(
+    part: fn(Input) -> anyhow::Result<String>,
+    input: &Rc<[u8]>,
+) -> (anyhow::Result<String>, Sample) {
+    PARSED.with(|parsed| parsed.set(None));
+    let begin = Instant::now();
+
+    let result = part(to_input(input));
+
+    let total = Instant::now().duration_since(begin);
+    let parse = PARSED
+        .with(|parsed| parsed.take())
+        .map(|parsed| parsed.duration_since(begin));
+    (result, Sample { total, parse })
+}
+
+fn to_input(data: &Rc<[u8]>) -> Input {
+    Input::new(Cursor::new(Rc::clone(data)))
+}
+
+pub fn run<I, T>(year: u16, solutions: &[Solution], args: I) -> anyhow::Result<()>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let opts = Opts::parse_from(args);
+    if opts.all {
+        return run_all(year, solutions, &opts);
+    }
+
+    VISUALIZE.with(|visualize| visualize.set(opts.visualize));
+
+    let day = opts.day.as_deref().context("No day given")?;
+    let solution = solutions
+        .iter()
+        .find(|solution| solution.matches(day))
+        .with_context(|| format!("No solution for day {day:?}"))?;
+
+    let input = resolve_input(opts.input.clone(), year, solution.day())?;
+    let input = read_input(&input)?;
+    let expected = match &opts.expect {
+        Some(path) => Expected::load(path, solution)?,
+        None => Expected::default(),
+    };
+
+    let parts = opts.parts();
+    let labelled = parts.len() > 1;
+
+    let mut failure = None;
+    let mut mismatches = 0;
+    for part in parts {
+        let label = if labelled {
+            format!(" {part}")
+        } else {
+            String::new()
+        };
+
+        let runs = opts.bench.unwrap_or(1).max(1);
+        let (mut result, sample) = measure(solution.part(part), &input);
+        let mut samples = vec![sample];
+        while result.is_ok() && samples.len() < runs {
+            let (next, sample) = measure(solution.part(part), &input);
+            result = next;
+            samples.push(sample);
+        }
+
+        if opts.time {
+            eprintln!("[Execution time{label}]: {:?}", samples[0].total);
+        }
+
+        if opts.bench.is_some() {
+            eprintln!("[Benchmark{label}]: {} runs", samples.len());
+            eprintln!(
+                "  total: {}",
+                Stats::new(samples.iter().map(|sample| sample.total))
+            );
+            if samples.iter().all(|sample| sample.parse.is_some()) {
+                let parse = Stats::new(samples.iter().filter_map(|sample| sample.parse));
+                let solve = Stats::new(samples.iter().filter_map(Sample::solve));
+                eprintln!("  parse: {parse}");
+                eprintln!("  solve: {solve}");
+            } else {
+                eprintln!("  parse: n/a, the part did not read its whole input");
+            }
+        }
+
+        let mismatch = result.as_ref().ok().and_then(|result| {
+            let expected = expected.get(part)?;
+            let diff = diff(&expected.to_string(), result)?;
+            Some(format!(
+                "{part} does not match the expected answer:\n{diff}"
+            ))
+        });
+
+        if opts.format == Format::Json {
+            let record = Record {
+                year,
+                day: solution.day(),
+                solution: solution.name,
+                part: part.number(),
+                answer: result.as_deref().ok(),
+                duration_ns: Stats::new(samples.iter().map(|sample| sample.total))
+                    .median
+                    .as_nanos(),
+                error: match &result {
+                    Ok(_) => mismatch.clone(),
+                    Err(err) => Some(format!("{err:?}")),
+                },
+            };
+            println!("{}", serde_json::to_string(&record)?);
+        } else {
+            match &result {
+                Ok(result) if labelled && result.contains('\n') => println!("{part}:\n{result}"),
+                Ok(result) if labelled => println!("{part}: {result}"),
+                Ok(result) => println!("{result}"),
+                Err(err) => {
+                    eprintln!("Execution failed!");
+                    eprintln!("===============================");
+                    eprintln!("{err:?}");
+                    eprintln!("===============================");
+                }
+            }
+            if let Some(mismatch) = &mismatch {
+                eprint!("{mismatch}");
+            }
+        }
+
+        // The diff is already part of the output, only count it.
+        if mismatch.is_some() {
+            mismatches += 1;
+        } else if let Err(err) = result {
+            failure.get_or_insert(err);
+        }
+    }
+
+    match (failure, mismatches) {
+        (Some(err), _) => Err(err),
+        (None, 0) => Ok(()),
+        (None, 1) => anyhow::bail!("1 answer did not match"),
+        (None, n) => anyhow::bail!("{n} answers did not match"),
+    }
+}
+
+/// Shortens multi-line answers, like letters drawn in a grid, to fit a table cell.
+fn summarize(answer: &str) -> String {
+    let answer = answer.trim_end();
+    match answer.lines().count() {
+        0 | 1 => answer.to_owned(),
+        lines => format!("[{lines} lines]"),
+    }
+}
+
+/// Aligns the cells in columns, rows with fewer cells than the header are
+/// padded with empty cells.
+fn format_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<_> = header.iter().map(|cell| cell.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let header = header.iter().map(|cell| cell.to_string()).collect();
+    let mut table = String::new();
+    for row in std::iter::once(&header).chain(rows) {
+        let line = row
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::repeat(""))
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .join("  ");
+        let _ = writeln!(table, "{}", line.trim_end());
+    }
+    table
+}
+
+const HEADER: [&str; 5] = ["Day", "Part 1", "Time", "Part 2", "Time"];
+
+/// Runs both parts of every day on its default input and prints a summary table.
+fn run_all(year: u16, solutions: &[Solution], opts: &Opts) -> anyhow::Result<()> {
+    let mut rows = Vec::new();
+    let mut failures = Vec::new();
+    let mut total = Duration::ZERO;
+
+    for solution in solutions {
+        let Ok(path) = resolve_input(None, year, solution.day()) else {
+            let mut row = vec![solution.name.to_owned(), "missing input".to_owned()];
+            row.resize(HEADER.len(), "-".to_owned());
+            rows.push(row);
+            continue;
+        };
+        let input = read_input(&path)?;
+        let expected = match &opts.expect {
+            Some(path) => Expected::load(path, solution)?,
+            None => Expected::default(),
+        };
+
+        let mut row = vec![solution.name.to_owned()];
+        for part in [Part::One, Part::Two] {
+            let (result, sample) = measure(solution.part(part), &input);
+            total += sample.total;
+
+            let cell = match result {
+                Ok(answer) if solution.stub == Some(part) => format!("{answer} (stub)"),
+                Ok(answer) => match expected.get(part) {
+                    Some(expected) if diff(&expected.to_string(), &answer).is_some() => {
+                        failures.push(format!(
+                            "{} {part} does not match the expected answer {expected}",
+                            solution.name
+                        ));
+                        format!("{} (wrong)", summarize(&answer))
+                    }
+                    _ => summarize(&answer),
+                },
+                Err(err) => {
+                    failures.push(format!("{} {part} failed: {err:#}", solution.name));
+                    "failed".to_owned()
+                }
+            };
+            row.push(cell);
+            row.push(format!("{:.2?}", sample.total));
+        }
+        rows.push(row);
+    }
+
+    print!("{}", format_table(&HEADER, &rows));
+    println!();
+    println!("Total runtime: {total:.2?}");
+
+    for failure in &failures {
+        eprintln!("{failure}");
+    }
+    match failures.len() {
+        0 => Ok(()),
+        n => anyhow::bail!("{n} parts failed"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solution(name: &'static str) -> Solution {
+        Solution {
+            name,
+            part1: |_| Ok(String::new()),
+            part2: |_| Ok(String::new()),
+            stub: None,
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(solution("day04").matches("4"));
+        assert!(solution("day04").matches("04"));
+        assert!(solution("day04").matches("day04"));
+        assert!(!solution("day04").matches("4_2"));
+        assert!(solution("day04_2").matches("4_2"));
+        assert!(solution("day15").matches("15"));
+        assert!(!solution("day15").matches("foo"));
+    }
+
+    #[test]
+    fn test_day() {
+        assert_eq!(solution("day04").day(), 4);
+        assert_eq!(solution("day04_2").day(), 4);
+        assert_eq!(solution("day15").day(), 15);
+    }
+
+    #[test]
+    fn test_summarize() {
+        assert_eq!(summarize("1234\n"), "1234");
+        assert_eq!(summarize("#..#\n#..#\n####\n"), "[3 lines]");
+    }
+
+    #[test]
+    fn test_format_table() {
+        let rows = [
+            vec!["day01".to_owned(), "7".to_owned(), "1ms".to_owned()],
+            vec!["day02".to_owned(), "missing input".to_owned()],
+        ];
+        assert_eq!(
+            format_table(&["Day", "Part 1", "Time"], &rows),
+            "Day    Part 1         Time\nday01  7              1ms\nday02  missing input\n"
+        );
+    }
+
+    #[test]
+    fn test_default_input() {
+        assert_eq!(
+            default_input(Path::new("inputs"), 2021, 4),
+            Path::new("inputs/2021/day04.txt")
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(diff("7", "7\n"), None);
+        assert_eq!(diff("7", "8"), Some("- 7\n+ 8\n".to_owned()));
+        assert_eq!(
+            diff("#\n#", "#\n.\n#"),
+            Some("  #\n- #\n+ .\n+ #\n".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_measure_parse() {
+        let input: Rc<[u8]> = b"1\n2\n".to_vec().into();
+        let (_, sample) = measure(
+            |mut input| Ok(input.lines_parsed::<u8>().count().to_string()),
+            &input,
+        );
+        assert!(sample.parse.is_some());
+
+        let (_, sample) = measure(|_| Ok(String::new()), &input);
+        assert!(sample.parse.is_none());
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::new([4, 1, 3, 2].into_iter().map(Duration::from_millis));
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_micros(2500));
+        assert_eq!(stats.mean, Duration::from_micros(2500));
+        assert_eq!(stats.stddev.as_micros(), 1118);
+    }
+}
@@ -0,0 +1,669 @@
+use crate::pnm;
+use anyhow::Context;
+use std::fmt;
+use std::io::BufRead;
+
+/// Offsets of the orthogonally adjacent cells, clockwise starting at the top.
+pub const OFFSETS4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+/// Offsets of the orthogonally and diagonally adjacent cells, in row-major order.
+pub const OFFSETS8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+#[derive(Debug, Clone)]
+pub struct Grid<T> {
+    data: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(data: Vec<T>, width: usize, height: usize) -> Self {
+        Self {
+            data,
+            width,
+            height,
+        }
+    }
+
+    /// A grid with the value of every cell computed from its position.
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut((usize, usize)) -> T) -> Self {
+        let data = (0..width * height)
+            .map(|index| f((index % width, index / width)))
+            .collect();
+        Self::new(data, width, height)
+    }
+
+    /// Parses a rectangular grid with one cell per byte, e.g. `#..#.`.
+    ///
+    /// The grid ends at the first blank line, leading blank lines as well as
+    /// trailing whitespace, like the `\r` of `\r\n`, are ignored.
+    pub fn try_parse(reader: impl BufRead) -> anyhow::Result<Self>
+    where
+        T: TryFrom<u8>,
+        T::Error: fmt::Display + fmt::Debug + Send + Sync + 'static,
+    {
+        Self::try_parse_with(reader, |byte| T::try_from(byte).map_err(anyhow::Error::msg))
+    }
+
+    /// Like `try_parse`, but converts the cells with `cell`, e.g. `grid::digit`.
+    pub fn try_parse_with(
+        reader: impl BufRead,
+        cell: impl FnMut(u8) -> anyhow::Result<T>,
+    ) -> anyhow::Result<Self> {
+        let lines = reader.lines().enumerate().map(|(i, line)| {
+            line.map(|line| (i + 1, line))
+                .with_context(|| format!("Failed to read line {}", i + 1))
+        });
+        Self::parse_lines(lines, cell)
+    }
+
+    /// Parses numbered lines, see `try_parse`.
+    pub(crate) fn parse_lines(
+        lines: impl Iterator<Item = anyhow::Result<(usize, String)>>,
+        mut cell: impl FnMut(u8) -> anyhow::Result<T>,
+    ) -> anyhow::Result<Self> {
+        let mut data = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+
+        for line in lines {
+            let (number, line) = line?;
+            let line = line.trim_end();
+            if line.is_empty() {
+                if height == 0 {
+                    continue;
+                }
+                break;
+            }
+
+            let width = *width.get_or_insert(line.len());
+            anyhow::ensure!(
+                line.len() == width,
+                "Line {number} has {} cells, expected {width}",
+                line.len()
+            );
+
+            for (column, byte) in line.bytes().enumerate() {
+                let value = cell(byte).with_context(|| {
+                    format!(
+                        "Invalid cell {:?} at line {number}, column {}",
+                        byte as char,
+                        column + 1
+                    )
+                })?;
+                data.push(value);
+            }
+            height += 1;
+        }
+
+        Ok(Self::new(data, width.unwrap_or(0), height))
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let index = x + y * self.width;
+        (index < self.data.len()).then(|| &self.data[index])
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let index = x + y * self.width;
+        (index < self.data.len()).then(|| &mut self.data[index])
+    }
+
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn into_data(self) -> Vec<T> {
+        self.data
+    }
+
+    pub fn swap(&mut self, pos1: (usize, usize), pos2: (usize, usize)) {
+        let index1 = pos1.0 + pos1.1 * self.width;
+        let index2 = pos2.0 + pos2.1 * self.width;
+        self.data.swap(index1, index2);
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn indices(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.width * self.height).map(move |index| (index % width, index / width))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = (usize, usize, &T)> + '_ {
+        let width = self.width;
+        (0..self.width * self.height)
+            .map(move |index| (index % width, index / width, &self.data[index]))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        assert!(row < self.height, "row {row} is outside of the grid");
+        let start = self.width * row;
+        &self.data[start..start + self.width]
+    }
+
+    pub fn row_mut(&mut self, row: usize) -> &mut [T] {
+        assert!(row < self.height, "row {row} is outside of the grid");
+        let start = self.width * row;
+        &mut self.data[start..start + self.width]
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> {
+        self.data.chunks(self.width.max(1))
+    }
+
+    pub fn rows_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut [T]> {
+        self.data.chunks_mut(self.width.max(1))
+    }
+
+    /// The cells of column `col` from top to bottom, `rev()` goes bottom to top.
+    pub fn column(&self, col: usize) -> impl DoubleEndedIterator<Item = &T> {
+        assert!(col < self.width, "column {col} is outside of the grid");
+        self.data[col..].iter().step_by(self.width)
+    }
+
+    /// The cells of column `col` from top to bottom, `rev()` goes bottom to top.
+    pub fn column_mut(&mut self, col: usize) -> impl DoubleEndedIterator<Item = &mut T> {
+        assert!(col < self.width, "column {col} is outside of the grid");
+        self.data[col..].iter_mut().step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl DoubleEndedIterator<Item = impl DoubleEndedIterator<Item = &T>> {
+        (0..self.width).map(move |col| self.column(col))
+    }
+
+    /// The cells from `pos`, excluding it, in the direction of `dir` up to the edge
+    /// of the grid, e.g. `ray(pos, (0, -1))` walks upwards.
+    pub fn ray(
+        &self,
+        pos: (usize, usize),
+        dir: (isize, isize),
+    ) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        std::iter::successors(self.offset(pos, dir), move |&pos| self.offset(pos, dir))
+            .map(|pos| (pos, &self[pos]))
+    }
+
+    /// Writes the grid as a PGM or PPM image, see `pnm::write`.
+    pub fn write_pnm<P: pnm::Pixel>(
+        &self,
+        writer: impl std::io::Write,
+        color: impl FnMut(&T) -> P,
+    ) -> std::io::Result<()> {
+        pnm::write(self, writer, color)
+    }
+
+    /// The grid mirrored along its diagonal, rows become columns.
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_fn(self.height, self.width, |(x, y)| self[(y, x)].clone())
+    }
+
+    /// The grid rotated by 90 degrees clockwise.
+    pub fn rotate_cw(&self) -> Self
+    where
+        T: Clone,
+    {
+        let height = self.height;
+        Self::from_fn(height, self.width, |(x, y)| {
+            self[(y, height - 1 - x)].clone()
+        })
+    }
+
+    /// The grid rotated by 90 degrees counterclockwise.
+    pub fn rotate_ccw(&self) -> Self
+    where
+        T: Clone,
+    {
+        let width = self.width;
+        Self::from_fn(self.height, width, |(x, y)| {
+            self[(width - 1 - y, x)].clone()
+        })
+    }
+
+    /// The grid mirrored horizontally, the left column becomes the right one.
+    pub fn flip_h(&self) -> Self
+    where
+        T: Clone,
+    {
+        let width = self.width;
+        Self::from_fn(width, self.height, |(x, y)| {
+            self[(width - 1 - x, y)].clone()
+        })
+    }
+
+    /// The grid mirrored vertically, the top row becomes the bottom one.
+    pub fn flip_v(&self) -> Self
+    where
+        T: Clone,
+    {
+        let height = self.height;
+        Self::from_fn(self.width, height, |(x, y)| {
+            self[(x, height - 1 - y)].clone()
+        })
+    }
+
+    /// A borrowed `width` x `height` part of the grid with its top left cell at `(x, y)`.
+    ///
+    /// Panics if the view does not fit into the grid.
+    pub fn view(&self, x: usize, y: usize, width: usize, height: usize) -> GridView<'_, T> {
+        assert!(
+            x + width <= self.width && y + height <= self.height,
+            "view {width}x{height} at ({x}, {y}) does not fit into the {}x{} grid",
+            self.width,
+            self.height
+        );
+        GridView {
+            grid: self,
+            origin: (x, y),
+            width,
+            height,
+        }
+    }
+
+    /// The grid repeated `nx` times horizontally and `ny` times vertically.
+    ///
+    /// `f` maps a cell of the original grid and the position of its tile,
+    /// `(0, 0)` being the top left one, to the cell of the tiled grid.
+    pub fn tile(&self, nx: usize, ny: usize, mut f: impl FnMut(&T, usize, usize) -> T) -> Self {
+        let (width, height) = (self.width, self.height);
+        Self::from_fn(width * nx, height * ny, |(x, y)| {
+            f(&self[(x % width, y % height)], x / width, y / height)
+        })
+    }
+
+    /// The position `offset` away from `pos`, if it is still inside the grid.
+    fn offset(&self, (x, y): (usize, usize), (dx, dy): (isize, isize)) -> Option<(usize, usize)> {
+        let x = x.checked_add_signed(dx).filter(|&x| x < self.width)?;
+        let y = y.checked_add_signed(dy).filter(|&y| y < self.height)?;
+        Some((x, y))
+    }
+
+    fn neighbors<'a>(
+        &'a self,
+        pos: (usize, usize),
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = ((usize, usize), &'a T)> + 'a {
+        offsets.iter().filter_map(move |&offset| {
+            let pos = self.offset(pos, offset)?;
+            Some((pos, &self[pos]))
+        })
+    }
+
+    /// The up to 4 orthogonally adjacent cells of `pos`.
+    pub fn neighbors4(
+        &self,
+        pos: (usize, usize),
+    ) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        self.neighbors(pos, &OFFSETS4)
+    }
+
+    /// The up to 8 orthogonally and diagonally adjacent cells of `pos`.
+    pub fn neighbors8(
+        &self,
+        pos: (usize, usize),
+    ) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        self.neighbors(pos, &OFFSETS8)
+    }
+
+    /// The 8 adjacent cells of `pos`, wrapping around the edges like on a torus.
+    pub fn neighbors_wrapping(
+        &self,
+        (x, y): (usize, usize),
+    ) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        OFFSETS8.iter().map(move |&(dx, dy)| {
+            let x = (x as isize + dx).rem_euclid(self.width as isize) as usize;
+            let y = (y as isize + dy).rem_euclid(self.height as isize) as usize;
+            ((x, y), &self[(x, y)])
+        })
+    }
+
+    /// The cells within `radius` of `pos`, a square of `2 * radius + 1` cells
+    /// per side in row-major order.
+    pub fn kernel_at(&self, pos: (usize, usize), radius: usize, border: Border<T>) -> Kernel<'_, T>
+    where
+        T: Copy,
+    {
+        Kernel::new(self, pos, radius, border)
+    }
+
+    /// The kernel of every cell of the grid, see `kernel_at`.
+    pub fn kernel(
+        &self,
+        radius: usize,
+        border: Border<T>,
+    ) -> impl Iterator<Item = ((usize, usize), Kernel<'_, T>)> + '_
+    where
+        T: Copy,
+    {
+        self.indices()
+            .map(move |pos| (pos, Kernel::new(self, pos, radius, border)))
+    }
+}
+
+impl<T: std::fmt::Display> std::fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for i in 0..self.data.len() {
+            if i > 0 && i % self.width == 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", self.data[i])?;
+        }
+        Ok(())
+    }
+}
+
+/// Cell conversion for grids of decimal digits, e.g. `2199943210`.
+pub fn digit(byte: u8) -> anyhow::Result<u8> {
+    anyhow::ensure!(byte.is_ascii_digit(), "Not a digit");
+    Ok(byte - b'0')
+}
+
+impl<T> std::ops::Index<usize> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.data[index]
+    }
+}
+
+impl<T> std::ops::Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
+        &self.data[x + y * self.width]
+    }
+}
+
+impl<T> std::ops::IndexMut<usize> for Grid<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.data[index]
+    }
+}
+
+impl<T> std::ops::IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
+        &mut self.data[x + y * self.width]
+    }
+}
+
+/// A borrowed rectangular part of a `Grid`, see `Grid::view`.
+///
+/// Positions are relative to the top left cell of the view.
+#[derive(Debug, Copy, Clone)]
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    origin: (usize, usize),
+    width: usize,
+    height: usize,
+}
+
+impl<'a, T> GridView<'a, T> {
+    pub fn get(&self, x: usize, y: usize) -> Option<&'a T> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.grid.get(self.origin.0 + x, self.origin.1 + y)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = (usize, usize, &'a T)> + 'a {
+        let Self {
+            grid,
+            origin,
+            width,
+            height,
+        } = *self;
+        (0..width * height).map(move |index| {
+            let (x, y) = (index % width, index / width);
+            (x, y, &grid[(origin.0 + x, origin.1 + y)])
+        })
+    }
+
+    /// Copies the view into its own grid.
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_fn(self.width, self.height, |pos| self[pos].clone())
+    }
+}
+
+impl<T> std::ops::Index<(usize, usize)> for GridView<'_, T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
+        assert!(
+            x < self.width && y < self.height,
+            "({x}, {y}) is outside of the view"
+        );
+        &self.grid[(self.origin.0 + x, self.origin.1 + y)]
+    }
+}
+
+/// How a `Kernel` treats positions outside of the grid.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Border<T> {
+    /// Yields the value instead.
+    Constant(T),
+    /// Yields the closest cell on the edge of the grid.
+    Clamp,
+    /// Wraps around to the opposite edge, like on a torus.
+    Wrap,
+    /// Skips the position, kernels on the edges yield fewer cells.
+    Skip,
+}
+
+pub struct Kernel<'a, T: Copy> {
+    grid: &'a Grid<T>,
+    position: (usize, usize),
+    radius: usize,
+    border: Border<T>,
+    current: usize,
+    size: usize,
+}
+
+impl<'a, T: Copy> Kernel<'a, T> {
+    fn new(grid: &'a Grid<T>, position: (usize, usize), radius: usize, border: Border<T>) -> Self {
+        Self {
+            grid,
+            position,
+            radius,
+            border,
+            current: 0,
+            size: radius * 2 + 1,
+        }
+    }
+}
+
+impl<'a, T: Copy> Iterator for Kernel<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (width, height) = (self.grid.width as isize, self.grid.height as isize);
+
+        while self.current < self.size * self.size {
+            let (dx, dy) = (self.current % self.size, self.current / self.size);
+            let (x, y) = (
+                self.position.0 as isize + dx as isize - self.radius as isize,
+                self.position.1 as isize + dy as isize - self.radius as isize,
+            );
+
+            self.current += 1;
+
+            if x >= 0 && x < width && y >= 0 && y < height {
+                return Some(self.grid[(x as usize, y as usize)]);
+            }
+
+            let (x, y) = match self.border {
+                Border::Constant(value) => return Some(value),
+                Border::Clamp => (x.clamp(0, width - 1), y.clamp(0, height - 1)),
+                Border::Wrap => (x.rem_euclid(width), y.rem_euclid(height)),
+                Border::Skip => continue,
+            };
+            return Some(self.grid[(x as usize, y as usize)]);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid<u8> {
+        Grid::new((0..9).collect(), 3, 3)
+    }
+
+    #[test]
+    fn test_try_parse() {
+        let grid = Grid::<u8>::try_parse("\r\n#.#  \r\n..#\r\n\r\n###".as_bytes()).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.data(), b"#.#..#");
+
+        let err = Grid::<u8>::try_parse("#.#\n.#\n".as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "Line 2 has 2 cells, expected 3");
+
+        let err = Grid::try_parse_with("12\n3x\n".as_bytes(), digit).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid cell 'x' at line 2, column 2");
+    }
+
+    #[test]
+    fn test_kernel() {
+        let grid = grid();
+        let kernel = |border| grid.kernel_at((0, 0), 1, border).collect::<Vec<_>>();
+        assert_eq!(kernel(Border::Constant(9)), vec![9, 9, 9, 9, 0, 1, 9, 3, 4]);
+        assert_eq!(kernel(Border::Clamp), vec![0, 0, 1, 0, 0, 1, 3, 3, 4]);
+        assert_eq!(kernel(Border::Wrap), vec![8, 6, 7, 2, 0, 1, 5, 3, 4]);
+        assert_eq!(kernel(Border::Skip), vec![0, 1, 3, 4]);
+
+        let kernel: Vec<_> = grid.kernel_at((1, 1), 2, Border::Skip).collect();
+        assert_eq!(kernel, (0..9).collect::<Vec<_>>());
+        assert_eq!(grid.kernel(1, Border::Skip).count(), 9);
+    }
+
+    #[test]
+    fn test_transformations() {
+        let grid = Grid::new((0..6).collect::<Vec<u8>>(), 3, 2);
+        let transposed = grid.transpose();
+        assert_eq!((transposed.width(), transposed.height()), (2, 3));
+        assert_eq!(transposed.data(), &[0, 3, 1, 4, 2, 5]);
+        assert_eq!(grid.rotate_cw().data(), &[3, 0, 4, 1, 5, 2]);
+        assert_eq!(grid.rotate_ccw().data(), &[2, 5, 1, 4, 0, 3]);
+        assert_eq!(grid.rotate_cw().rotate_ccw().data(), grid.data());
+        assert_eq!(grid.flip_h().data(), &[2, 1, 0, 5, 4, 3]);
+        assert_eq!(grid.flip_v().data(), &[3, 4, 5, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_view() {
+        let grid = grid();
+        let view = grid.view(1, 1, 2, 2);
+        assert_eq!(view[(0, 0)], 4);
+        assert_eq!(view.get(1, 1), Some(&8));
+        assert_eq!(view.get(2, 0), None);
+        assert_eq!(view.to_grid().data(), &[4, 5, 7, 8]);
+        assert_eq!(grid.view(0, 0, 3, 3).values().count(), 9);
+    }
+
+    #[test]
+    fn test_tile() {
+        let grid = Grid::new(vec![1, 2], 2, 1);
+        let tiled = grid.tile(2, 2, |&v, tx, ty| v + 10 * tx + 20 * ty);
+        assert_eq!((tiled.width(), tiled.height()), (4, 2));
+        assert_eq!(tiled.data(), &[1, 2, 11, 12, 21, 22, 31, 32]);
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let mut grid = grid();
+        let rows: Vec<_> = grid.rows().rev().collect();
+        assert_eq!(rows, vec![&[6, 7, 8], &[3, 4, 5], &[0, 1, 2]]);
+        assert_eq!(grid.row(1), &[3, 4, 5]);
+
+        let columns: Vec<Vec<_>> = grid.columns().map(|c| c.rev().copied().collect()).collect();
+        assert_eq!(columns, vec![vec![6, 3, 0], vec![7, 4, 1], vec![8, 5, 2]]);
+
+        grid.column_mut(2).rev().for_each(|v| *v += 10);
+        grid.rows_mut().next().unwrap()[0] = 20;
+        assert_eq!(grid.data(), &[20, 1, 12, 3, 4, 15, 6, 7, 18]);
+    }
+
+    #[test]
+    #[should_panic(expected = "column 3 is outside of the grid")]
+    fn test_column_outside() {
+        grid().column(3).count();
+    }
+
+    #[test]
+    #[should_panic(expected = "column 0 is outside of the grid")]
+    fn test_column_empty() {
+        Grid::<u8>::new(Vec::new(), 0, 0).column(0).count();
+    }
+
+    #[test]
+    fn test_ray() {
+        let grid = grid();
+        let ray: Vec<_> = grid.ray((0, 2), (1, -1)).collect();
+        assert_eq!(ray, vec![((1, 1), &4), ((2, 0), &2)]);
+        assert_eq!(grid.ray((1, 0), (0, -1)).count(), 0);
+        let left: Vec<_> = grid.ray((2, 1), OFFSETS4[3]).map(|(_, &v)| v).collect();
+        assert_eq!(left, vec![4, 3]);
+    }
+
+    #[test]
+    fn test_neighbors4() {
+        let grid = grid();
+        let neighbors: Vec<_> = grid.neighbors4((0, 0)).collect();
+        assert_eq!(neighbors, vec![((1, 0), &1), ((0, 1), &3)]);
+        assert_eq!(grid.neighbors4((1, 1)).count(), 4);
+    }
+
+    #[test]
+    fn test_neighbors8() {
+        let grid = grid();
+        let neighbors: Vec<_> = grid.neighbors8((2, 2)).map(|(_, &v)| v).collect();
+        assert_eq!(neighbors, vec![4, 5, 7]);
+        assert_eq!(grid.neighbors8((1, 1)).count(), 8);
+    }
+
+    #[test]
+    fn test_neighbors_wrapping() {
+        let grid = grid();
+        let neighbors: Vec<_> = grid.neighbors_wrapping((0, 0)).map(|(_, &v)| v).collect();
+        assert_eq!(neighbors, vec![8, 6, 7, 2, 1, 5, 3, 4]);
+    }
+}
@@ -0,0 +1,238 @@
+use crate::grid::Grid;
+use anyhow::Context;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+use std::str::FromStr;
+
+/// The puzzle input of a part.
+///
+/// Besides being a plain `BufRead` it has helpers to parse the common input
+/// layouts, their errors carry the line (and column) of the offending input.
+pub struct Input {
+    reader: BufReader<Box<dyn Read>>,
+    line: usize,
+}
+
+impl Input {
+    pub fn new(reader: impl Read + 'static) -> Self {
+        Self {
+            reader: BufReader::new(Box::new(MarkEnd(reader))),
+            line: 0,
+        }
+    }
+
+    fn numbered_lines(&mut self) -> impl Iterator<Item = anyhow::Result<(usize, String)>> + '_ {
+        let Self { reader, line } = self;
+        reader.lines().map(move |result| {
+            *line += 1;
+            let number = *line;
+            result
+                .map(|content| (number, content))
+                .with_context(|| format!("Failed to read line {number}"))
+        })
+    }
+
+    /// Yields every line parsed as `T`.
+    pub fn lines_parsed<T>(&mut self) -> impl Iterator<Item = anyhow::Result<T>> + '_
+    where
+        T: FromStr,
+        T::Err: fmt::Display + fmt::Debug + Send + Sync + 'static,
+    {
+        self.numbered_lines().map(|line| {
+            let (number, line) = line?;
+            line.parse()
+                .map_err(anyhow::Error::msg)
+                .with_context(|| format!("Failed to parse line {number}: {line:?}"))
+        })
+    }
+
+    /// Yields groups of lines which are separated by blank lines.
+    pub fn blocks(&mut self) -> impl Iterator<Item = anyhow::Result<Vec<String>>> + '_ {
+        let mut lines = self.numbered_lines();
+        std::iter::from_fn(move || {
+            let mut block = Vec::new();
+            for line in lines.by_ref() {
+                let (_, line) = match line {
+                    Ok(line) => line,
+                    Err(err) => return Some(Err(err)),
+                };
+
+                if !line.trim().is_empty() {
+                    block.push(line);
+                } else if !block.is_empty() {
+                    break;
+                }
+            }
+            (!block.is_empty()).then_some(Ok(block))
+        })
+    }
+
+    /// Parses comma separated values, e.g. `3,4,3,1,2`.
+    pub fn csv_numbers<T>(&mut self) -> anyhow::Result<Vec<T>>
+    where
+        T: FromStr,
+        T::Err: fmt::Display + fmt::Debug + Send + Sync + 'static,
+    {
+        let mut result = Vec::new();
+        for line in self.numbered_lines() {
+            let (number, line) = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let mut column = 1;
+            for field in line.split(',') {
+                let value = field.trim();
+                let value_column = column + field.len() - field.trim_start().len();
+                let value = value.parse().map_err(anyhow::Error::msg).with_context(|| {
+                    format!("Failed to parse {value:?} at line {number}, column {value_column}")
+                })?;
+                result.push(value);
+                column += field.len() + 1;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Parses a rectangular grid with one cell per byte, see `Grid::try_parse`.
+    pub fn grid<T>(&mut self) -> anyhow::Result<Grid<T>>
+    where
+        T: TryFrom<u8>,
+        T::Error: fmt::Display + fmt::Debug + Send + Sync + 'static,
+    {
+        self.grid_with(|byte| T::try_from(byte).map_err(anyhow::Error::msg))
+    }
+
+    /// Like `grid`, but converts the cells with `cell`, e.g. `grid::digit`.
+    pub fn grid_with<T>(
+        &mut self,
+        cell: impl FnMut(u8) -> anyhow::Result<T>,
+    ) -> anyhow::Result<Grid<T>> {
+        Grid::parse_lines(self.numbered_lines(), cell)
+    }
+}
+
+impl From<&'static str> for Input {
+    fn from(input: &'static str) -> Self {
+        Self::new(input.as_bytes())
+    }
+}
+
+/// Declares the puzzle examples of a day and generates a `#[test]` for every
+/// expected answer, feeding the example through the part as an in-memory `Input`.
+///
+/// A leading newline of the example is stripped, so it can start on its own line:
+///
+/// ```ignore
+/// aoc_common::examples! {
+///     example: "
+/// 199
+/// 200
+/// 208
+/// " => { part1: 2, part2: 0 }
+/// }
+/// ```
+#[macro_export]
+macro_rules! examples {
+    ($($name:ident: $input:expr => { $($part:ident: $answer:expr),+ $(,)? })*) => {
+        #[cfg(test)]
+        mod examples {
+            $(
+                mod $name {
+                    $(
+                        #[test]
+                        fn $part() {
+                            let input: &'static str = $input;
+                            let input = input.strip_prefix('\n').unwrap_or(input);
+                            let answer = super::super::$part($crate::Input::from(input));
+                            assert_eq!(answer.unwrap(), $answer);
+                        }
+                    )+
+                }
+            )*
+        }
+    };
+}
+
+/// Reading the end of the input ends parsing for `--bench`, unless the part
+/// marks it itself with `cli::parsed`.
+struct MarkEnd<R>(R);
+
+impl<R: Read> Read for MarkEnd<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.0.read(buf)?;
+        if read == 0 && !buf.is_empty() {
+            crate::cli::reached_end();
+        }
+        Ok(read)
+    }
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl BufRead for Input {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.reader.consume(amt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(s: &'static str) -> Input {
+        Input::new(s.as_bytes())
+    }
+
+    #[test]
+    fn test_lines_parsed() {
+        let values = input("1\n2\n3\n")
+            .lines_parsed::<u32>()
+            .collect::<Result<Vec<_>, _>>();
+        assert_eq!(values.unwrap(), vec![1, 2, 3]);
+
+        let err = input("1\nx\n")
+            .lines_parsed::<u32>()
+            .nth(1)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Failed to parse line 2: \"x\"");
+    }
+
+    #[test]
+    fn test_blocks() {
+        let blocks = input("\na\nb\n\n\nc\n")
+            .blocks()
+            .collect::<Result<Vec<_>, _>>();
+        assert_eq!(blocks.unwrap(), vec![vec!["a", "b"], vec!["c"]]);
+    }
+
+    #[test]
+    fn test_csv_numbers() {
+        assert_eq!(
+            input("3,4, 3,1\n").csv_numbers::<u8>().unwrap(),
+            vec![3, 4, 3, 1]
+        );
+
+        let err = input("3,4, x,1\n").csv_numbers::<u8>().unwrap_err();
+        assert_eq!(err.to_string(), "Failed to parse \"x\" at line 1, column 6");
+    }
+
+    #[test]
+    fn test_grid() {
+        let grid = input("\n12\n34\n\n56\n").grid::<u8>().unwrap();
+        assert_eq!((grid.width(), grid.height()), (2, 2));
+        assert_eq!(grid.data(), b"1234");
+
+        let err = input("12\n345\n").grid::<u8>().unwrap_err();
+        assert_eq!(err.to_string(), "Line 2 has 3 cells, expected 2");
+    }
+}
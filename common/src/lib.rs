@@ -0,0 +1,9 @@
+pub mod automaton;
+pub mod cli;
+pub mod grid;
+pub mod input;
+pub mod pnm;
+pub mod search;
+pub mod sparse_grid;
+
+pub use input::Input;
@@ -0,0 +1,152 @@
+//! Netpbm images of grids, binary PGM for grayscale and PPM for colour.
+//!
+//! Both formats are understood by most image viewers and converters, e.g.
+//! `ffmpeg -i frames/%05d.ppm out.gif` turns a sequence of frames into an animation.
+
+use crate::grid::Grid;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// A pixel colour, `u8` is a gray level and `[u8; 3]` an RGB colour.
+pub trait Pixel: Copy {
+    /// Magic number of the image format.
+    const MAGIC: &'static str;
+    /// File extension of the image format.
+    const EXTENSION: &'static str;
+
+    fn push(self, buffer: &mut Vec<u8>);
+}
+
+impl Pixel for u8 {
+    const MAGIC: &'static str = "P5";
+    const EXTENSION: &'static str = "pgm";
+
+    fn push(self, buffer: &mut Vec<u8>) {
+        buffer.push(self);
+    }
+}
+
+impl Pixel for [u8; 3] {
+    const MAGIC: &'static str = "P6";
+    const EXTENSION: &'static str = "ppm";
+
+    fn push(self, buffer: &mut Vec<u8>) {
+        buffer.extend_from_slice(&self);
+    }
+}
+
+/// Writes `grid` as an image with one pixel per cell, coloured by `color`.
+pub fn write<T, P: Pixel>(
+    grid: &Grid<T>,
+    mut writer: impl Write,
+    mut color: impl FnMut(&T) -> P,
+) -> io::Result<()> {
+    let mut buffer =
+        format!("{}\n{} {}\n255\n", P::MAGIC, grid.width(), grid.height()).into_bytes();
+    for cell in grid.data() {
+        color(cell).push(&mut buffer);
+    }
+    writer.write_all(&buffer)
+}
+
+/// Writes a sequence of images into a directory, e.g. one per step of a simulation.
+///
+/// The frames are numbered, `00000.ppm`, `00001.ppm` and so on.
+#[derive(Debug)]
+pub struct Frames {
+    dir: PathBuf,
+    next: usize,
+}
+
+impl Frames {
+    /// Creates `dir` if it does not exist yet and removes the frames of an
+    /// earlier run from it, other files are kept.
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if is_frame(&path) {
+                std::fs::remove_file(path)?;
+            }
+        }
+        Ok(Self { dir, next: 0 })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The number of frames written so far.
+    pub fn len(&self) -> usize {
+        self.next
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.next == 0
+    }
+
+    /// Writes the next frame and returns its path.
+    pub fn write<T, P: Pixel>(
+        &mut self,
+        grid: &Grid<T>,
+        color: impl FnMut(&T) -> P,
+    ) -> io::Result<PathBuf> {
+        let path = self.dir.join(format!("{:05}.{}", self.next, P::EXTENSION));
+        let mut writer = BufWriter::new(File::create(&path)?);
+        write(grid, &mut writer, color)?;
+        writer.flush()?;
+        self.next += 1;
+        Ok(path)
+    }
+}
+
+/// Whether `path` is named like a frame, e.g. `00042.ppm`.
+fn is_frame(path: &Path) -> bool {
+    let stem = path.file_stem().and_then(|stem| stem.to_str());
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    let numbered =
+        stem.is_some_and(|stem| stem.len() >= 5 && stem.bytes().all(|b| b.is_ascii_digit()));
+    numbered && matches!(extension, Some(u8::EXTENSION | <[u8; 3]>::EXTENSION))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write() {
+        let grid = Grid::new(vec![true, false, false, true], 2, 2);
+
+        let mut pgm = Vec::new();
+        write(&grid, &mut pgm, |&on| if on { 255u8 } else { 0 }).unwrap();
+        assert_eq!(pgm, b"P5\n2 2\n255\n\xff\x00\x00\xff");
+
+        let mut ppm = Vec::new();
+        write(&grid, &mut ppm, |&on| [on as u8, 2, 3]).unwrap();
+        assert_eq!(&ppm[..11], b"P6\n2 2\n255\n");
+        assert_eq!(&ppm[11..], &[1, 2, 3, 0, 2, 3, 0, 2, 3, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_frames() {
+        let dir = std::env::temp_dir().join(format!("aoc-frames-{}", std::process::id()));
+        let mut frames = Frames::new(&dir).unwrap();
+        let grid = Grid::new(vec![0u8; 4], 2, 2);
+        frames.write(&grid, |&v| v).unwrap();
+        let path = frames.write(&grid, |&v| [v; 3]).unwrap();
+        assert_eq!(path, dir.join("00001.ppm"));
+        assert_eq!(frames.len(), 2);
+        assert!(dir.join("00000.pgm").exists());
+
+        // A new, shorter run does not leave frames of the earlier one behind.
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+        let mut frames = Frames::new(&dir).unwrap();
+        frames.write(&grid, |&v| v).unwrap();
+        assert!(dir.join("00000.pgm").exists());
+        assert!(!dir.join("00001.ppm").exists());
+        assert!(dir.join("notes.txt").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
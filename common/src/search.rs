@@ -0,0 +1,302 @@
+use crate::grid::Grid;
+use fxhash::{FxHashMap, FxHashSet};
+use std::collections::hash_map::Entry;
+use std::collections::BinaryHeap;
+use std::hash::Hash;
+use std::ops::Add;
+
+type Pos = (usize, usize);
+
+/// A path found by a search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<S, C> {
+    /// The states from the start to the goal, both included.
+    pub states: Vec<S>,
+    pub cost: C,
+}
+
+struct Node<S, C> {
+    state: S,
+    parent: Option<usize>,
+    cost: C,
+}
+
+fn path<S: Clone, C: Copy>(nodes: &[Node<S, C>], index: usize) -> Path<S, C> {
+    let cost = nodes[index].cost;
+    let mut states = Vec::new();
+    let mut current = Some(index);
+    while let Some(index) = current {
+        states.push(nodes[index].state.clone());
+        current = nodes[index].parent;
+    }
+    states.reverse();
+    Path { states, cost }
+}
+
+/// Breadth first search, the cost of the path is its number of steps.
+pub fn bfs<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, usize>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut seen = FxHashSet::default();
+    seen.insert(start.clone());
+
+    // The nodes double as the queue, everything after `current` is still to visit.
+    let mut nodes = vec![Node {
+        state: start,
+        parent: None,
+        cost: 0,
+    }];
+    let mut current = 0;
+    while current < nodes.len() {
+        if is_goal(&nodes[current].state) {
+            return Some(path(&nodes, current));
+        }
+
+        let cost = nodes[current].cost + 1;
+        for state in successors(&nodes[current].state) {
+            if seen.insert(state.clone()) {
+                nodes.push(Node {
+                    state,
+                    parent: Some(current),
+                    cost,
+                });
+            }
+        }
+        current += 1;
+    }
+
+    None
+}
+
+/// All states reachable from `start`, in breadth first order.
+pub fn bfs_reachable<S, I>(start: S, mut successors: impl FnMut(&S) -> I) -> Vec<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut seen = FxHashSet::default();
+    seen.insert(start.clone());
+
+    let mut states = vec![start];
+    let mut current = 0;
+    while current < states.len() {
+        for state in successors(&states[current]) {
+            if seen.insert(state.clone()) {
+                states.push(state);
+            }
+        }
+        current += 1;
+    }
+
+    states
+}
+
+/// Dijkstra's algorithm, successors yield the next state and the cost of the step.
+pub fn dijkstra<S, C, I>(
+    start: S,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(start, successors, |_| C::default(), is_goal)
+}
+
+struct Candidate<C> {
+    estimate: C,
+    cost: C,
+    index: usize,
+}
+
+impl<C: Ord> PartialEq for Candidate<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl<C: Ord> Eq for Candidate<C> {}
+
+impl<C: Ord> PartialOrd for Candidate<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C: Ord> Ord for Candidate<C> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // Reversed, the `BinaryHeap` has to yield the lowest estimate first.
+        other.estimate.cmp(&self.estimate)
+    }
+}
+
+/// A* search, `heuristic` estimates the remaining cost to a goal and must
+/// never overestimate it.
+pub fn astar<S, C, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut candidates = BinaryHeap::new();
+    candidates.push(Candidate {
+        estimate: heuristic(&start),
+        cost: C::default(),
+        index: 0,
+    });
+
+    let mut indices = FxHashMap::default();
+    indices.insert(start.clone(), 0);
+    let mut nodes = vec![Node {
+        state: start,
+        parent: None,
+        cost: C::default(),
+    }];
+
+    while let Some(Candidate { cost, index, .. }) = candidates.pop() {
+        if cost > nodes[index].cost {
+            // A cheaper way to this state was found after queueing it.
+            continue;
+        }
+        if is_goal(&nodes[index].state) {
+            return Some(path(&nodes, index));
+        }
+
+        for (state, step) in successors(&nodes[index].state) {
+            let cost = cost + step;
+            let next = match indices.entry(state) {
+                Entry::Vacant(entry) => {
+                    let next = nodes.len();
+                    nodes.push(Node {
+                        state: entry.key().clone(),
+                        parent: Some(index),
+                        cost,
+                    });
+                    entry.insert(next);
+                    next
+                }
+                Entry::Occupied(entry) => {
+                    let next = *entry.get();
+                    if cost >= nodes[next].cost {
+                        continue;
+                    }
+                    nodes[next].cost = cost;
+                    nodes[next].parent = Some(index);
+                    next
+                }
+            };
+
+            candidates.push(Candidate {
+                estimate: cost + heuristic(&nodes[next].state),
+                cost,
+                index: next,
+            });
+        }
+    }
+
+    None
+}
+
+/// Manhattan distance between two grid positions, a heuristic for `astar` on grids.
+pub fn manhattan(a: Pos, b: Pos) -> usize {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
+
+/// Successors for searches on a grid, moving to the orthogonal neighbours.
+///
+/// `cost` returns the cost of entering a cell or `None` if it can not be entered.
+pub fn grid_successors<'a, T, C>(
+    grid: &'a Grid<T>,
+    mut cost: impl FnMut(Pos, &T) -> Option<C> + 'a,
+) -> impl FnMut(&Pos) -> Vec<(Pos, C)> + 'a {
+    move |&pos| {
+        grid.neighbors4(pos)
+            .filter_map(|(pos, cell)| Some((pos, cost(pos, cell)?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1 1 9
+    // 9 1 9
+    // 9 1 1
+    fn grid() -> Grid<u8> {
+        Grid::new(vec![1, 1, 9, 9, 1, 9, 9, 1, 1], 3, 3)
+    }
+
+    #[test]
+    fn test_bfs() {
+        let grid = grid();
+        let path = bfs(
+            (0, 0),
+            |&pos| {
+                grid.neighbors4(pos)
+                    .filter(|(_, &cell)| cell == 1)
+                    .map(|(pos, _)| pos)
+            },
+            |&pos| pos == (2, 2),
+        );
+        let path = path.unwrap();
+        assert_eq!(path.cost, 4);
+        assert_eq!(path.states, vec![(0, 0), (1, 0), (1, 1), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn test_bfs_reachable() {
+        let grid = grid();
+        let reachable = bfs_reachable((0, 0), |&pos| {
+            grid.neighbors4(pos)
+                .filter(|(_, &cell)| cell == 1)
+                .map(|(pos, _)| pos)
+        });
+        assert_eq!(reachable.len(), 5);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let grid = grid();
+        let path = dijkstra(
+            (0, 0),
+            grid_successors(&grid, |_, &cell| Some(cell)),
+            |&pos| pos == (2, 2),
+        );
+        assert_eq!(path.unwrap().cost, 4);
+
+        let path = dijkstra(
+            (0, 0),
+            grid_successors(&grid, |_, &cell| (cell == 1).then_some(1)),
+            |&pos| pos == (2, 0),
+        );
+        assert_eq!(path, None);
+    }
+
+    #[test]
+    fn test_astar() {
+        let grid = grid();
+        let path = astar(
+            (0, 0),
+            grid_successors(&grid, |_, &cell| Some(cell as usize)),
+            |&pos| manhattan(pos, (2, 2)),
+            |&pos| pos == (2, 2),
+        );
+        let path = path.unwrap();
+        assert_eq!(path.cost, 4);
+        assert_eq!(path.states, vec![(0, 0), (1, 0), (1, 1), (1, 2), (2, 2)]);
+    }
+}
@@ -0,0 +1,241 @@
+use crate::grid::Grid;
+use fxhash::FxHashMap;
+
+type Pos = (isize, isize);
+
+/// Inclusive bounding box of the positions in a `SparseGrid`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: Pos,
+    pub max: Pos,
+}
+
+impl Bounds {
+    fn new(pos: Pos) -> Self {
+        Self { min: pos, max: pos }
+    }
+
+    fn include(&mut self, (x, y): Pos) {
+        self.min = (self.min.0.min(x), self.min.1.min(y));
+        self.max = (self.max.0.max(x), self.max.1.max(y));
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max.0 - self.min.0 + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max.1 - self.min.1 + 1) as usize
+    }
+
+    pub fn contains(&self, (x, y): Pos) -> bool {
+        (self.min.0..=self.max.0).contains(&x) && (self.min.1..=self.max.1).contains(&y)
+    }
+
+    /// The bounds grown by `by` on every side.
+    pub fn expand(&self, by: isize) -> Self {
+        Self {
+            min: (self.min.0 - by, self.min.1 - by),
+            max: (self.max.0 + by, self.max.1 + by),
+        }
+    }
+
+    /// All positions within the bounds in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let Self { min, max } = *self;
+        (min.1..=max.1).flat_map(move |y| (min.0..=max.0).map(move |x| (x, y)))
+    }
+}
+
+/// A grid which only stores the cells which are set, keyed by signed coordinates.
+///
+/// The bounds cover every position inserted so far, they never shrink.
+#[derive(Debug, Clone)]
+pub struct SparseGrid<T> {
+    cells: FxHashMap<Pos, T>,
+    bounds: Option<Bounds>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self {
+            cells: FxHashMap::default(),
+            bounds: None,
+        }
+    }
+
+    pub fn insert(&mut self, pos: Pos, value: T) -> Option<T> {
+        self.include(pos);
+        self.cells.insert(pos, value)
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.cells.get(&pos)
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.cells.get_mut(&pos)
+    }
+
+    /// The cell at `pos`, inserting the value returned by `f` if it is not set yet.
+    pub fn get_or_insert_with(&mut self, pos: Pos, f: impl FnOnce() -> T) -> &mut T {
+        self.include(pos);
+        self.cells.entry(pos).or_insert_with(f)
+    }
+
+    fn include(&mut self, pos: Pos) {
+        match &mut self.bounds {
+            Some(bounds) => bounds.include(pos),
+            None => self.bounds = Some(Bounds::new(pos)),
+        }
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The set cells in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        self.bounds
+            .iter()
+            .flat_map(Bounds::positions)
+            .filter_map(|pos| Some((pos, self.get(pos)?)))
+    }
+
+    /// The set cells in arbitrary order, cheaper than `iter` for large bounds.
+    pub fn cells(&self) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        self.cells.iter().map(|(&pos, value)| (pos, value))
+    }
+
+    /// A dense grid covering the bounds, cells which are not set are `default`.
+    ///
+    /// Also returns the origin, the position of the top left cell of the dense
+    /// grid, so `from_grid` can convert it back. An empty grid has the origin `(0, 0)`.
+    pub fn to_grid(&self, default: T) -> (Pos, Grid<T>)
+    where
+        T: Clone,
+    {
+        let Some(bounds) = self.bounds else {
+            return ((0, 0), Grid::new(Vec::new(), 0, 0));
+        };
+
+        let data = bounds
+            .positions()
+            .map(|pos| self.get(pos).unwrap_or(&default).clone())
+            .collect();
+        (bounds.min, Grid::new(data, bounds.width(), bounds.height()))
+    }
+
+    /// Sets every cell of `grid`, its top left cell at `origin`.
+    pub fn from_grid(grid: Grid<T>, (x0, y0): Pos) -> Self {
+        let width = grid.width();
+        grid.into_data()
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| {
+                (
+                    (x0 + (i % width) as isize, y0 + (i / width) as isize),
+                    value,
+                )
+            })
+            .collect()
+    }
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<(Pos, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Pos, T)>>(iter: I) -> Self {
+        let mut grid = Self::new();
+        for (pos, value) in iter {
+            grid.insert(pos, value);
+        }
+        grid
+    }
+}
+
+/// Sets every cell of the grid with its top left cell at `(0, 0)`, use
+/// `SparseGrid::from_grid` to place it elsewhere.
+impl<T> From<Grid<T>> for SparseGrid<T> {
+    fn from(grid: Grid<T>) -> Self {
+        Self::from_grid(grid, (0, 0))
+    }
+}
+
+/// Renders the cells within the bounds, cells which are not set are rendered as a space.
+impl<T: std::fmt::Display> std::fmt::Display for SparseGrid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(bounds) = self.bounds else {
+            return Ok(());
+        };
+
+        for (x, y) in bounds.positions() {
+            if x == bounds.min.0 && y > bounds.min.1 {
+                writeln!(f)?;
+            }
+            match self.get((x, y)) {
+                Some(value) => write!(f, "{value}")?,
+                None => write!(f, " ")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        let grid: SparseGrid<_> = [((-2, 1), 'a'), ((3, -1), 'b')].into_iter().collect();
+        let bounds = grid.bounds().unwrap();
+        assert_eq!(bounds.min, (-2, -1));
+        assert_eq!(bounds.max, (3, 1));
+        assert_eq!((bounds.width(), bounds.height()), (6, 3));
+    }
+
+    #[test]
+    fn test_iter() {
+        let grid: SparseGrid<_> = [((1, 1), 'c'), ((5, -1), 'b'), ((0, -1), 'a')]
+            .into_iter()
+            .collect();
+        let cells: Vec<_> = grid.iter().map(|(_, &c)| c).collect();
+        assert_eq!(cells, vec!['a', 'b', 'c']);
+    }
+
+    #[test]
+    fn test_grid_conversion() {
+        let grid: SparseGrid<_> = [((-1, 0), 1), ((1, 1), 2)].into_iter().collect();
+        let (origin, dense) = grid.to_grid(0);
+        assert_eq!(origin, (-1, 0));
+        assert_eq!(dense.data(), &[1, 0, 0, 0, 0, 2]);
+
+        let sparse = SparseGrid::from_grid(dense.clone(), origin);
+        assert_eq!(sparse.len(), 6);
+        assert_eq!(sparse.get((-1, 0)), Some(&1));
+        assert_eq!(sparse.get((1, 1)), Some(&2));
+        assert_eq!(sparse.bounds(), grid.bounds());
+
+        let sparse = SparseGrid::from(dense);
+        assert_eq!(sparse.get((2, 1)), Some(&2));
+    }
+
+    #[test]
+    fn test_display() {
+        let grid: SparseGrid<_> = [((-1, -1), '#'), ((1, 0), '#')].into_iter().collect();
+        assert_eq!(grid.to_string(), "#  \n  #");
+    }
+}